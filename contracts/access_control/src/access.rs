@@ -1,13 +1,24 @@
 use crate::errors::AccessControlError;
-use soroban_sdk::{contracttype, panic_with_error, Address, Env};
+use soroban_sdk::{contracttype, panic_with_error, Address, Env, Vec};
 use utils::bump::bump_instance;
-use utils::storage_errors::StorageError;
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum Role {
+    Admin = 0,
+    Operator = 1,
+    FeeManager = 2,
+    Pauser = 3,
+}
 
 #[derive(Clone)]
 #[contracttype]
 enum DataKey {
     Admin,
     FutureAdmin,
+    FutureAdminDeadline,
+    RoleMembers(Role),
 }
 
 #[derive(Clone)]
@@ -27,7 +38,15 @@ pub trait AccessControlTrait {
     fn require_admin(&self);
     fn get_future_admin(&self) -> Option<Address>;
     fn set_future_admin(&self, admin: &Address);
+    fn get_future_admin_deadline(&self) -> Option<u64>;
+    fn set_future_admin_deadline(&self, deadline: u64);
+    fn remove_future_admin(&self);
     fn perform_admin_check(&self) -> Result<Address, AccessControlError>;
+    fn get_role_members(&self, role: &Role) -> Vec<Address>;
+    fn has_role(&self, role: &Role, user: &Address) -> bool;
+    fn grant_role(&self, role: &Role, user: &Address);
+    fn revoke_role(&self, role: &Role, user: &Address);
+    fn check_role(&self, role: &Role, user: &Address);
 }
 
 impl AccessControlTrait for AccessControl {
@@ -66,10 +85,7 @@ impl AccessControlTrait for AccessControl {
 
     fn get_future_admin(&self) -> Option<Address> {
         bump_instance(&self.0);
-        match self.0.storage().instance().get(&DataKey::FutureAdmin) {
-            Some(v) => v,
-            None => panic_with_error!(&self.0, StorageError::ValueNotInitialized),
-        }
+        self.0.storage().instance().get(&DataKey::FutureAdmin)
     }
 
     fn set_future_admin(&self, admin: &Address) {
@@ -80,10 +96,90 @@ impl AccessControlTrait for AccessControl {
             .set(&DataKey::FutureAdmin, admin)
     }
 
+    fn get_future_admin_deadline(&self) -> Option<u64> {
+        bump_instance(&self.0);
+        self.0
+            .storage()
+            .instance()
+            .get(&DataKey::FutureAdminDeadline)
+    }
+
+    fn set_future_admin_deadline(&self, deadline: u64) {
+        bump_instance(&self.0);
+        self.0
+            .storage()
+            .instance()
+            .set(&DataKey::FutureAdminDeadline, &deadline)
+    }
+
+    fn remove_future_admin(&self) {
+        bump_instance(&self.0);
+        self.0.storage().instance().remove(&DataKey::FutureAdmin);
+        self.0
+            .storage()
+            .instance()
+            .remove(&DataKey::FutureAdminDeadline);
+    }
+
     fn perform_admin_check(&self) -> Result<Address, AccessControlError> {
         if !self.has_admin() {
             panic_with_error!(&self.0, AccessControlError::AdminNotFound);
         }
         self.get_admin().ok_or(AccessControlError::AdminNotFound)
     }
+
+    // admin role is backed by the admin storage and changed via admin transfer only
+    fn get_role_members(&self, role: &Role) -> Vec<Address> {
+        bump_instance(&self.0);
+        match role {
+            Role::Admin => match self.get_admin() {
+                Some(admin) => Vec::from_array(&self.0, [admin]),
+                None => Vec::new(&self.0),
+            },
+            _ => self
+                .0
+                .storage()
+                .instance()
+                .get(&DataKey::RoleMembers(*role))
+                .unwrap_or(Vec::new(&self.0)),
+        }
+    }
+
+    fn has_role(&self, role: &Role, user: &Address) -> bool {
+        self.get_role_members(role).contains(user)
+    }
+
+    fn grant_role(&self, role: &Role, user: &Address) {
+        if *role == Role::Admin {
+            panic_with_error!(&self.0, AccessControlError::AdminRoleNotGrantable);
+        }
+        let mut members = self.get_role_members(role);
+        if !members.contains(user) {
+            members.push_back(user.clone());
+            self.0
+                .storage()
+                .instance()
+                .set(&DataKey::RoleMembers(*role), &members);
+        }
+    }
+
+    fn revoke_role(&self, role: &Role, user: &Address) {
+        if *role == Role::Admin {
+            panic_with_error!(&self.0, AccessControlError::AdminRoleNotGrantable);
+        }
+        let mut members = self.get_role_members(role);
+        if let Some(index) = members.first_index_of(user) {
+            members.remove(index);
+            self.0
+                .storage()
+                .instance()
+                .set(&DataKey::RoleMembers(*role), &members);
+        }
+    }
+
+    fn check_role(&self, role: &Role, user: &Address) {
+        if !self.has_role(role, user) {
+            panic_with_error!(&self.0, AccessControlError::UserHasNoRole);
+        }
+    }
 }
@@ -7,4 +7,8 @@ pub enum AccessControlError {
     AdminNotFound = 101,
     UserNotAdmin = 102,
     AdminAlreadySet = 103,
+    FutureAdminNotFound = 104,
+    AdminTransferExpired = 105,
+    UserHasNoRole = 106,
+    AdminRoleNotGrantable = 107,
 }
@@ -1,2 +1,27 @@
 pub(crate) const COMPLETED_REQUESTS_PAGE_SIZE: u32 = 100;
 pub(crate) const DESTINATIONS_PAGE_SIZE: u32 = 100;
+pub(crate) const DEFAULT_REQUEST_TTL: u64 = 7 * 24 * 60 * 60;
+// ledger entries a single transaction is allowed to write, network setting
+pub(crate) const TX_MAX_WRITE_LEDGER_ENTRIES: u32 = 25;
+// batch intake writes once the caller's auth nonce, contract instance, accumulated & collected
+// fees of the input token, deposit wallet allowance and balances of the wallet and the contract
+pub(crate) const BATCH_INTAKE_WRITE_ENTRIES: u32 = 7;
+// and per request: request itself, its tx id, its links, links of the destination's last
+// pending request and the destination's index
+pub(crate) const REQUEST_INTAKE_WRITE_ENTRIES: u32 = 5;
+// upper bound of configurable batch size, it's the ledger entries written rather than cpu
+// budget what limits the batch. batch of deposits from different wallets or tokens, or swap
+// batch routed through several pools writes more entries and has to be smaller
+pub(crate) const MAX_BATCH_SIZE_LIMIT: u32 =
+    (TX_MAX_WRITE_LEDGER_ENTRIES - BATCH_INTAKE_WRITE_ENTRIES) / REQUEST_INTAKE_WRITE_ENTRIES;
+pub(crate) const DEFAULT_MAX_BATCH_SIZE: u32 = MAX_BATCH_SIZE_LIMIT;
+// spot price is probed with this share of the swapped amount, small enough for its price impact
+// to be negligible
+pub(crate) const SPOT_PROBE_DIVISOR: u128 = 1000;
+// swap output may be below oracle-implied value by 5% at most
+pub(crate) const DEFAULT_MAX_ORACLE_DEVIATION_BPS: u32 = 500;
+// oracle price older than 15 minutes isn't used as a bound
+pub(crate) const DEFAULT_MAX_ORACLE_PRICE_AGE: u64 = 15 * 60;
+
+// version of events topics & payload layout, see events.rs
+pub(crate) const EVENTS_VERSION: u32 = 1;
@@ -1,23 +1,392 @@
 use soroban_sdk::token::TokenClient as SorobanTokenClient;
 use soroban_sdk::{contract, contractimpl, panic_with_error, Address, BytesN, Env, Map, Vec};
 
-use access_control::access::{AccessControl, AccessControlTrait};
+use access_control::access::{AccessControl, AccessControlTrait, Role};
+use access_control::errors::AccessControlError;
+use utils::constant::BASIS_POINTS;
+use utils::fee::{calculate_fee, FeeSchedule};
+use utils::math_errors::MathError;
+use utils::storage_errors::StorageError;
 
+use crate::constants::MAX_BATCH_SIZE_LIMIT;
 use crate::errors::PoolError;
+use crate::events::Events;
 use crate::interfaces::{PoolContractInterface, UpgradeableContract};
-use crate::swap_router::swap_with_router;
+use crate::oracle::check_oracle_deviation;
+use crate::venue::{SwapRoute, SwapVenue, Venue};
 
 use crate::storage::{
-    add_proxy_wallet, add_swap_request, get_active_swap_requests,
-    get_completed_swap_requests_last_page, get_completed_swap_requests_page, get_destinations,
-    get_destinations_last_page, get_last_operation_id, get_operational_fee, get_operator,
-    get_proxy_wallets, get_swap_request_by_id, get_swap_router, set_operational_fee, set_operator,
-    set_swap_request_processed, set_swap_router, SwapRequest,
+    add_collected_fee, add_proxy_wallet, add_swap_request, get_accumulated_fees,
+    get_active_swap_requests, get_collected_fees, get_completed_swap_requests_last_page,
+    get_completed_swap_requests_page, get_destination_preferences, get_destinations,
+    get_destinations_last_page, get_fee_treasury, get_last_operation_id, get_legacy_operator,
+    get_legacy_swap_requests, get_max_batch_size, get_max_operational_fee,
+    get_max_oracle_deviation, get_max_oracle_price_age, get_operation_id_floor,
+    get_operation_id_window, get_operational_fee, get_price_oracle, get_proxy_wallet_info,
+    get_proxy_wallets, get_proxy_wallets_by_token, get_refund_address, get_request_ttl,
+    get_swap_request_by_id, get_swap_request_with_status, get_swap_router, get_tx_operation_id,
+    get_venue, get_venues, is_operation_id_consumed, is_paused, is_proxy_wallet_enabled,
+    is_tx_id_consumed, remove_proxy_wallet, remove_venue, set_accumulated_fees, set_collected_fees,
+    set_destination_preferences, set_fee_treasury, set_legacy_swap_requests, set_max_batch_size,
+    set_max_operational_fee, set_max_oracle_deviation, set_max_oracle_price_age,
+    set_operation_id_floor, set_operation_id_window, set_operational_fee, set_paused,
+    set_price_oracle, set_proxy_wallet_enabled, set_proxy_wallet_info, set_refund_address,
+    set_request_ttl, set_swap_request_expired, set_swap_request_processed,
+    set_swap_request_refunded, set_swap_router, set_venue, take_legacy_operator,
+    CompletedSwapRequest, DestinationPreferences, PauseTarget, ProxyWalletInfo, RequestDescriptor,
+    RequestQuote, RequestStatus, SwapRequest,
 };
 
 #[contract]
 pub struct PoolContract;
 
+fn require_not_paused(e: &Env, targets: &[PauseTarget]) {
+    for target in targets {
+        if is_paused(e, target) {
+            panic_with_error!(e, PoolError::Paused);
+        }
+    }
+}
+
+fn validate_fee_schedule(e: &Env, token: &Address, schedule: &FeeSchedule) {
+    if schedule.flat < 0 || schedule.min < 0 || schedule.max.unwrap_or(0) < 0 {
+        panic_with_error!(e, PoolError::NegativeFee);
+    }
+    if schedule.rate_bps > BASIS_POINTS {
+        panic_with_error!(e, PoolError::InvalidFeeRate);
+    }
+    if schedule.max.is_some_and(|max| max < schedule.min) {
+        panic_with_error!(e, PoolError::InvalidFeeBounds);
+    }
+    if let Some(max_fee) = get_max_operational_fee(e, token) {
+        // percentage part is unbounded unless schedule sets its own cap
+        let above_max = match schedule.max {
+            Some(max) => max > max_fee,
+            None => schedule.rate_bps > 0,
+        };
+        if schedule.flat > max_fee || schedule.min > max_fee || above_max {
+            panic_with_error!(e, PoolError::FeeAboveMaximum);
+        }
+    }
+}
+
+// operational fee limited by the admin-configured ceiling, which may be lowered after schedule is set
+fn calculate_operational_fee(e: &Env, token: &Address, amount: i128) -> i128 {
+    let fee = calculate_fee(e, &get_operational_fee(e, token), amount);
+    match get_max_operational_fee(e, token) {
+        Some(max_fee) if fee > max_fee => max_fee,
+        _ => fee,
+    }
+}
+
+// operator configured before the upgrade to roles is granted the role on first access
+fn migrate_legacy_operator(e: &Env) {
+    if let Some(operator) = take_legacy_operator(e) {
+        AccessControl::new(e).grant_role(&Role::Operator, &operator);
+        Events::new(e).role_granted(Role::Operator, &operator);
+    }
+}
+
+fn require_operator(e: &Env, operator: &Address) {
+    operator.require_auth();
+    migrate_legacy_operator(e);
+    if !AccessControl::new(e).has_role(&Role::Operator, operator) {
+        panic_with_error!(e, PoolError::UnauthorizedOperator);
+    }
+}
+
+// registers a single deposit, operator is expected to be authorized by the caller
+fn add_request_internal(
+    e: &Env,
+    proxy_wallet: Address,
+    tx_id: BytesN<32>,
+    op_id: u128,
+    destination: Address,
+    token_in: Address,
+    amount_in: i128,
+) {
+    require_not_paused(
+        e,
+        &[PauseTarget::Intake, PauseTarget::Token(token_in.clone())],
+    );
+
+    let proxy_wallets = get_proxy_wallets(e);
+    let token_out = match proxy_wallets.get(proxy_wallet.clone()) {
+        Some(value) => value,
+        None => {
+            panic_with_error!(e, PoolError::UnauthorizedProxyWallet);
+        }
+    };
+    if !is_proxy_wallet_enabled(e, &proxy_wallet) {
+        panic_with_error!(e, PoolError::ProxyWalletDisabled);
+    }
+    // provider restrictions are enforced only for wallets configured with metadata
+    let wallet_info = get_proxy_wallet_info(e, &proxy_wallet);
+    if let Some(info) = &wallet_info {
+        if !info.allowed_tokens_in.contains(&token_in) {
+            panic_with_error!(e, PoolError::TokenInNotAllowed);
+        }
+        if amount_in < info.min_amount {
+            panic_with_error!(e, PoolError::DepositBelowMinimum);
+        }
+        if let Some(max_amount) = info.max_amount {
+            if amount_in > max_amount {
+                panic_with_error!(e, PoolError::DepositAboveMaximum);
+            }
+        }
+    }
+
+    // destination may choose output token if provider allows it, payout address and slippage
+    let mut token_out = token_out;
+    let mut recipient = destination.clone();
+    let mut max_slippage_bps = None;
+    if let Some(preferences) = get_destination_preferences(e, &destination) {
+        if wallet_info
+            .as_ref()
+            .is_some_and(|info| info.allowed_tokens_out.contains(&preferences.token_out))
+        {
+            token_out = preferences.token_out;
+        }
+        recipient = preferences.payout_address;
+        max_slippage_bps = Some(preferences.max_slippage_bps);
+    }
+    require_not_paused(e, &[PauseTarget::Token(token_out.clone())]);
+
+    // operation ids below the window are rejected, within the window they may
+    // arrive out of order but each of them is accepted only once
+    let window_start = get_last_operation_id(e).saturating_sub(get_operation_id_window(e));
+    let below_floor = get_operation_id_floor(e).is_some_and(|floor| op_id <= floor);
+    if op_id <= window_start || below_floor || is_operation_id_consumed(e, op_id) {
+        panic_with_error!(e, PoolError::OperationIdAlreadyConsumed);
+    }
+    // check the same classic deposit isn't credited twice
+    if is_tx_id_consumed(e, &tx_id) {
+        panic_with_error!(e, PoolError::TxIdAlreadyConsumed);
+    }
+
+    let token_in_client = SorobanTokenClient::new(e, &token_in);
+    token_in_client.transfer_from(
+        &e.current_contract_address(),
+        &proxy_wallet,
+        &e.current_contract_address(),
+        &amount_in,
+    );
+
+    let operational_fee = calculate_operational_fee(e, &token_in, amount_in);
+    // request with nothing left to swap could never be fulfilled
+    if operational_fee >= amount_in {
+        panic_with_error!(e, PoolError::FeeExceedsAmount);
+    }
+    // fee stays in the contract until withdrawn to treasury
+    if operational_fee > 0 {
+        add_collected_fee(e, &token_in, operational_fee);
+    }
+
+    let swap_request = SwapRequest {
+        tx_id,
+        op_id,
+        destination: destination.clone(),
+        token_in,
+        amount_in: amount_in - operational_fee,
+        fee: operational_fee,
+        token_out,
+        recipient,
+        max_slippage_bps,
+        proxy_wallet,
+        created_at: e.ledger().timestamp(),
+        created_ledger: e.ledger().sequence(),
+        // max ttl is used to effectively disable expiry
+        expires_at: e.ledger().timestamp().saturating_add(get_request_ttl(e)),
+    };
+    add_swap_request(e, &destination, &swap_request);
+
+    Events::new(e).request_added(&swap_request);
+}
+
+// fulfills single request swapping on the given venue
+fn fulfill_swap(
+    e: &Env,
+    destination: &Address,
+    op_id: u128,
+    venue: &Venue,
+    venue_id: Option<u32>,
+    swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+    out_min: i128,
+) -> i128 {
+    let swap_request = get_swap_request_by_id(e, destination, op_id);
+    require_not_paused(
+        e,
+        &[
+            PauseTarget::Swaps,
+            PauseTarget::Token(swap_request.token_in.clone()),
+            PauseTarget::Token(swap_request.token_out.clone()),
+        ],
+    );
+
+    venue.validate(
+        e,
+        swaps_chain,
+        &swap_request.token_in,
+        &swap_request.token_out,
+    );
+
+    // spot price has to be taken before the swap moves it
+    let slippage_reference = slippage_reference(
+        e,
+        venue,
+        swaps_chain,
+        &swap_request.token_in,
+        &swap_request.token_out,
+        swap_request.amount_in,
+        swap_request.max_slippage_bps,
+    );
+    let amount_out = swap_verified(
+        e,
+        venue,
+        swaps_chain,
+        &swap_request.token_in,
+        &swap_request.token_out,
+        swap_request.amount_in,
+        out_min,
+    );
+    require_within_slippage(e, slippage_reference, amount_out);
+    require_oracle_bound(
+        e,
+        &swap_request.token_in,
+        &swap_request.token_out,
+        swap_request.amount_in,
+        amount_out,
+    );
+
+    // transfer swap result to destination
+    SorobanTokenClient::new(e, &swap_request.token_out).transfer(
+        &e.current_contract_address(),
+        &swap_request.recipient,
+        &amount_out,
+    );
+
+    // mark swap as processed
+    set_swap_request_processed(e, destination, swap_request.clone(), amount_out, venue_id);
+
+    Events::new(e).request_fulfilled(&swap_request, amount_out);
+
+    amount_out
+}
+
+// swaps on the venue. contract balance is checked to not rely on router report
+fn swap_verified(
+    e: &Env,
+    venue: &Venue,
+    swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+    token_in: &Address,
+    token_out: &Address,
+    amount_in: i128,
+    out_min: i128,
+) -> i128 {
+    let token_out_client = SorobanTokenClient::new(e, token_out);
+    let balance_before = token_out_client.balance(&e.current_contract_address());
+    let reported_amount_out = venue.swap(
+        e,
+        swaps_chain,
+        token_in,
+        token_out,
+        amount_in as u128,
+        out_min as u128,
+    ) as i128;
+    let amount_out = token_out_client.balance(&e.current_contract_address()) - balance_before;
+    if amount_out != reported_amount_out {
+        panic_with_error!(e, PoolError::SwapOutputMismatch);
+    }
+    if amount_out < out_min {
+        panic_with_error!(e, PoolError::SwapOutputBelowMinimum);
+    }
+    amount_out
+}
+
+// spot output to check the swap against, if any slippage limit is set.
+// reference is taken after pools fees, so the limit bounds price impact only
+fn slippage_reference(
+    e: &Env,
+    venue: &Venue,
+    swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+    token_in: &Address,
+    token_out: &Address,
+    amount_in: i128,
+    max_slippage_bps: Option<u32>,
+) -> Option<(i128, u32)> {
+    max_slippage_bps.map(|bps| {
+        let spot_out =
+            venue.spot_amount_out(e, swaps_chain, token_in, token_out, amount_in as u128);
+        (spot_out as i128, bps)
+    })
+}
+
+// oracle bound is optional and applies only once oracle is configured
+fn require_oracle_bound(
+    e: &Env,
+    token_in: &Address,
+    token_out: &Address,
+    amount_in: i128,
+    amount_out: i128,
+) {
+    if let Some(oracle) = get_price_oracle(e) {
+        check_oracle_deviation(
+            e,
+            &oracle,
+            get_max_oracle_deviation(e),
+            get_max_oracle_price_age(e),
+            token_in,
+            token_out,
+            amount_in,
+            amount_out,
+        );
+    }
+}
+
+// lowest output within slippage limit of the spot output
+fn slippage_min_out(e: &Env, spot_out: i128, bps: u32) -> i128 {
+    match spot_out.checked_mul((BASIS_POINTS - bps) as i128) {
+        Some(v) => v / BASIS_POINTS as i128,
+        None => panic_with_error!(e, MathError::NumberOverflow),
+    }
+}
+
+fn require_within_slippage(e: &Env, reference: Option<(i128, u32)>, amount_out: i128) {
+    if let Some((spot_out, bps)) = reference {
+        if amount_out < slippage_min_out(e, spot_out, bps) {
+            panic_with_error!(e, PoolError::SlippageExceeded);
+        }
+    }
+}
+
+// address funds of the request are returned to, unless admin sends them elsewhere
+fn refund_target(e: &Env, swap_request: &SwapRequest) -> Address {
+    match get_refund_address(e) {
+        Some(address) => address,
+        None => swap_request.proxy_wallet.clone(),
+    }
+}
+
+// splits amount out proportionally to the amounts in, rounding down.
+// rounding remainder goes to the last share so the whole amount is distributed
+fn split_pro_rata(e: &Env, amount_out: i128, amounts_in: &Vec<i128>, total_in: i128) -> Vec<i128> {
+    let mut shares = Vec::new(e);
+    let mut distributed = 0;
+    for (i, amount_in) in amounts_in.iter().enumerate() {
+        let share = if i as u32 == amounts_in.len() - 1 {
+            amount_out - distributed
+        } else {
+            match amount_out.checked_mul(amount_in) {
+                Some(v) => v / total_in,
+                None => panic_with_error!(e, MathError::NumberOverflow),
+            }
+        };
+        distributed += share;
+        shares.push_back(share);
+    }
+    shares
+}
+
 #[contractimpl]
 impl PoolContractInterface for PoolContract {
     // admin methods
@@ -29,10 +398,108 @@ impl PoolContractInterface for PoolContract {
         access_control.set_admin(&admin);
     }
 
+    fn propose_admin(e: Env, new_admin: Address, deadline: Option<u64>) {
+        let access_control = AccessControl::new(&e);
+        access_control.require_admin();
+
+        // previous proposal, if any, is overwritten along with its deadline
+        access_control.remove_future_admin();
+        access_control.set_future_admin(&new_admin);
+        if let Some(deadline) = deadline {
+            if deadline < e.ledger().timestamp() {
+                panic_with_error!(&e, AccessControlError::AdminTransferExpired);
+            }
+            access_control.set_future_admin_deadline(deadline);
+        }
+
+        Events::new(&e).admin_transfer_proposed(
+            &access_control.get_admin().unwrap(),
+            &new_admin,
+            deadline,
+        );
+    }
+
+    fn accept_admin(e: Env) {
+        let access_control = AccessControl::new(&e);
+        let future_admin = match access_control.get_future_admin() {
+            Some(v) => v,
+            None => panic_with_error!(&e, AccessControlError::FutureAdminNotFound),
+        };
+        future_admin.require_auth();
+
+        if let Some(deadline) = access_control.get_future_admin_deadline() {
+            if e.ledger().timestamp() > deadline {
+                panic_with_error!(&e, AccessControlError::AdminTransferExpired);
+            }
+        }
+
+        let previous_admin = access_control.get_admin().unwrap();
+        access_control.set_admin(&future_admin);
+        access_control.remove_future_admin();
+
+        Events::new(&e).admin_transfer_accepted(&previous_admin, &future_admin);
+    }
+
+    fn cancel_admin_transfer(e: Env) {
+        let access_control = AccessControl::new(&e);
+        access_control.require_admin();
+
+        let future_admin = match access_control.get_future_admin() {
+            Some(v) => v,
+            None => panic_with_error!(&e, AccessControlError::FutureAdminNotFound),
+        };
+        access_control.remove_future_admin();
+
+        Events::new(&e)
+            .admin_transfer_cancelled(&access_control.get_admin().unwrap(), &future_admin);
+    }
+
+    // replaces all the operators with the given one
     fn set_operator(e: Env, operator: Address) {
         let access_control = AccessControl::new(&e);
         access_control.require_admin();
-        set_operator(&e, &operator);
+        migrate_legacy_operator(&e);
+        let events = Events::new(&e);
+        for member in access_control.get_role_members(&Role::Operator) {
+            access_control.revoke_role(&Role::Operator, &member);
+            events.role_revoked(Role::Operator, &member);
+        }
+        access_control.grant_role(&Role::Operator, &operator);
+        events.role_granted(Role::Operator, &operator);
+    }
+
+    fn grant_role(e: Env, role: Role, user: Address) {
+        let access_control = AccessControl::new(&e);
+        access_control.require_admin();
+        migrate_legacy_operator(&e);
+        access_control.grant_role(&role, &user);
+        Events::new(&e).role_granted(role, &user);
+    }
+
+    fn revoke_role(e: Env, role: Role, user: Address) {
+        let access_control = AccessControl::new(&e);
+        access_control.require_admin();
+        migrate_legacy_operator(&e);
+        access_control.check_role(&role, &user);
+        access_control.revoke_role(&role, &user);
+        Events::new(&e).role_revoked(role, &user);
+    }
+
+    fn pause(e: Env, user: Address, target: PauseTarget) {
+        user.require_auth();
+        let access_control = AccessControl::new(&e);
+        if !access_control.has_role(&Role::Admin, &user) {
+            access_control.check_role(&Role::Pauser, &user);
+        }
+        set_paused(&e, &target, true);
+        Events::new(&e).paused(&user, &target);
+    }
+
+    fn unpause(e: Env, user: Address, target: PauseTarget) {
+        user.require_auth();
+        AccessControl::new(&e).check_admin(&user);
+        set_paused(&e, &target, false);
+        Events::new(&e).unpaused(&user, &target);
     }
 
     fn add_proxy_wallet(e: Env, proxy_wallet: Address, token_out: Address) {
@@ -41,12 +508,124 @@ impl PoolContractInterface for PoolContract {
         add_proxy_wallet(&e, &proxy_wallet, &token_out);
     }
 
+    fn remove_proxy_wallet(e: Env, proxy_wallet: Address) {
+        let access_control = AccessControl::new(&e);
+        access_control.require_admin();
+        remove_proxy_wallet(&e, &proxy_wallet);
+    }
+
+    fn set_proxy_wallet_info(e: Env, proxy_wallet: Address, info: ProxyWalletInfo) {
+        let access_control = AccessControl::new(&e);
+        access_control.require_admin();
+        if !get_proxy_wallets(&e).contains_key(proxy_wallet.clone()) {
+            panic_with_error!(&e, PoolError::UnauthorizedProxyWallet);
+        }
+        if info.min_amount < 0 || info.max_amount.unwrap_or(info.min_amount) < info.min_amount {
+            panic_with_error!(&e, PoolError::InvalidDepositLimits);
+        }
+        set_proxy_wallet_info(&e, &proxy_wallet, &info);
+    }
+
+    fn set_proxy_wallet_enabled(e: Env, proxy_wallet: Address, enabled: bool) {
+        let access_control = AccessControl::new(&e);
+        access_control.require_admin();
+        if !get_proxy_wallets(&e).contains_key(proxy_wallet.clone()) {
+            panic_with_error!(&e, PoolError::UnauthorizedProxyWallet);
+        }
+        set_proxy_wallet_enabled(&e, &proxy_wallet, enabled);
+    }
+
+    fn register_venue(e: Env, venue_id: u32, venue: Venue) {
+        let access_control = AccessControl::new(&e);
+        access_control.require_admin();
+        set_venue(&e, venue_id, &venue);
+    }
+
+    fn remove_venue(e: Env, venue_id: u32) {
+        let access_control = AccessControl::new(&e);
+        access_control.require_admin();
+        remove_venue(&e, venue_id);
+    }
+
+    fn get_venues(e: Env) -> Map<u32, Venue> {
+        get_venues(&e)
+    }
+
     fn set_swap_router(e: Env, swap_router: Address) {
         let access_control = AccessControl::new(&e);
         access_control.require_admin();
         set_swap_router(&e, &swap_router);
     }
 
+    fn set_request_ttl(e: Env, ttl: u64) {
+        let access_control = AccessControl::new(&e);
+        access_control.require_admin();
+        set_request_ttl(&e, &ttl);
+    }
+
+    fn set_operation_id_window(e: Env, window: u128) {
+        let access_control = AccessControl::new(&e);
+        access_control.require_admin();
+        if window > 0 && get_operation_id_floor(&e).is_none() {
+            set_operation_id_floor(&e, &Some(get_last_operation_id(&e)));
+        }
+        set_operation_id_window(&e, &window);
+    }
+
+    fn set_max_batch_size(e: Env, max_batch_size: u32) {
+        let access_control = AccessControl::new(&e);
+        access_control.require_admin();
+        if max_batch_size == 0 || max_batch_size > MAX_BATCH_SIZE_LIMIT {
+            panic_with_error!(&e, PoolError::InvalidBatchSize);
+        }
+        set_max_batch_size(&e, &max_batch_size);
+    }
+
+    fn set_refund_address(e: Env, refund_address: Option<Address>) {
+        let access_control = AccessControl::new(&e);
+        access_control.require_admin();
+        set_refund_address(&e, &refund_address);
+    }
+
+    fn migrate_legacy_requests(
+        e: Env,
+        destination: Address,
+        refund_wallet: Address,
+        limit: u32,
+    ) -> u32 {
+        let access_control = AccessControl::new(&e);
+        access_control.require_admin();
+
+        let mut legacy_requests = get_legacy_swap_requests(&e, &destination);
+        let count = limit.min(legacy_requests.len());
+        // legacy requests had no fee, refunds go to the refund address if set, otherwise
+        // to the wallet admin has provided
+        for request in legacy_requests.slice(..count) {
+            add_swap_request(
+                &e,
+                &destination,
+                &SwapRequest {
+                    tx_id: request.tx_id,
+                    op_id: request.op_id,
+                    destination: request.destination.clone(),
+                    token_in: request.token_in,
+                    amount_in: request.amount_in,
+                    fee: 0,
+                    token_out: request.token_out,
+                    recipient: request.destination,
+                    max_slippage_bps: None,
+                    proxy_wallet: refund_wallet.clone(),
+                    created_at: e.ledger().timestamp(),
+                    created_ledger: e.ledger().sequence(),
+                    expires_at: e.ledger().timestamp().saturating_add(get_request_ttl(&e)),
+                },
+            );
+        }
+        legacy_requests = legacy_requests.slice(count..);
+        set_legacy_swap_requests(&e, &destination, &legacy_requests);
+        legacy_requests.len()
+    }
+
     fn add_request(
         e: Env,
         operator: Address,
@@ -58,102 +637,487 @@ impl PoolContractInterface for PoolContract {
         amount_in: i128,
     ) {
         // check operator is whitelisted
-        operator.require_auth();
-        if operator != get_operator(&e) {
-            panic_with_error!(&e, PoolError::UnauthorizedOperator);
+        require_operator(&e, &operator);
+        add_request_internal(
+            &e,
+            proxy_wallet,
+            tx_id,
+            op_id,
+            destination,
+            token_in,
+            amount_in,
+        );
+    }
+
+    fn add_requests_batch(e: Env, operator: Address, requests: Vec<RequestDescriptor>) {
+        require_operator(&e, &operator);
+        if requests.is_empty() {
+            panic_with_error!(&e, PoolError::EmptyBatch);
+        }
+        if requests.len() > get_max_batch_size(&e) {
+            panic_with_error!(&e, PoolError::BatchTooLarge);
         }
 
-        let proxy_wallets = get_proxy_wallets(&e);
-        let token_out = match proxy_wallets.get(proxy_wallet.clone()) {
-            Some(value) => value,
-            None => {
-                panic_with_error!(&e, PoolError::UnauthorizedProxyWallet);
+        // batch is applied atomically, any failed item reverts the whole batch
+        for request in requests.iter() {
+            add_request_internal(
+                &e,
+                request.proxy_wallet,
+                request.tx_id,
+                request.op_id,
+                request.destination,
+                request.token_in,
+                request.amount_in,
+            );
+        }
+    }
+
+    fn swap_chained_via_router(
+        e: Env,
+        operator: Address,
+        destination: Address,
+        op_id: u128,
+        swaps_chain: Vec<(Vec<Address>, BytesN<32>, Address)>,
+        out_min: i128,
+    ) -> i128 {
+        require_operator(&e, &operator);
+
+        fulfill_swap(
+            &e,
+            &destination,
+            op_id,
+            &Venue::Router(get_swap_router(&e)),
+            None,
+            &swaps_chain,
+            out_min,
+        )
+    }
+
+    fn swap_via_venue(
+        e: Env,
+        operator: Address,
+        destination: Address,
+        op_id: u128,
+        venue_id: u32,
+        swaps_chain: Vec<(Vec<Address>, BytesN<32>, Address)>,
+        out_min: i128,
+    ) -> i128 {
+        require_operator(&e, &operator);
+        fulfill_swap(
+            &e,
+            &destination,
+            op_id,
+            &get_venue(&e, venue_id),
+            Some(venue_id),
+            &swaps_chain,
+            out_min,
+        )
+    }
+
+    fn swap_best_execution(
+        e: Env,
+        operator: Address,
+        destination: Address,
+        op_id: u128,
+        routes: Vec<SwapRoute>,
+        out_min: i128,
+    ) -> i128 {
+        require_operator(&e, &operator);
+        if routes.is_empty() {
+            panic_with_error!(&e, PoolError::NoRoutesProvided);
+        }
+
+        // estimate every candidate, first route wins on equal output. candidates which can't be
+        // estimated are skipped, so a stale route doesn't revert the whole fulfillment
+        let swap_request = get_swap_request_by_id(&e, &destination, op_id);
+        let amount_in = swap_request.amount_in as u128;
+        let venues = get_venues(&e);
+        let mut best: Option<(SwapRoute, Venue, u128)> = None;
+        let mut best_spot_out: Option<u128> = None;
+        for route in routes.iter() {
+            let venue = match venues.get(route.venue_id) {
+                Some(v) => v,
+                None => continue,
+            };
+            let estimated_out = match venue.try_estimate(
+                &e,
+                &route.swaps_chain,
+                &swap_request.token_in,
+                &swap_request.token_out,
+                amount_in,
+            ) {
+                Some(v) => v,
+                None => continue,
+            };
+            if swap_request.max_slippage_bps.is_some() {
+                if let Some(spot_out) = venue.try_spot_amount_out(
+                    &e,
+                    &route.swaps_chain,
+                    &swap_request.token_in,
+                    &swap_request.token_out,
+                    amount_in,
+                ) {
+                    best_spot_out = Some(best_spot_out.map_or(spot_out, |v| v.max(spot_out)));
+                }
+            }
+            if best
+                .as_ref()
+                .map_or(true, |(_, _, best_out)| estimated_out > *best_out)
+            {
+                best = Some((route, venue, estimated_out));
             }
+        }
+
+        let (route, venue, estimated_out) = match best {
+            Some(v) => v,
+            None => panic_with_error!(&e, PoolError::NoViableRoute),
         };
+        // slippage is measured against the best spot price among candidates, so a venue can't pass
+        // the check by its own flat curve. the best quote failing it means no route would do better
+        if let (Some(bps), Some(spot_out)) = (swap_request.max_slippage_bps, best_spot_out) {
+            if (estimated_out as i128) < slippage_min_out(&e, spot_out as i128, bps) {
+                panic_with_error!(&e, PoolError::SlippageExceeded);
+            }
+        }
+        fulfill_swap(
+            &e,
+            &destination,
+            op_id,
+            &venue,
+            Some(route.venue_id),
+            &route.swaps_chain,
+            out_min,
+        )
+    }
 
-        // check if operation id already consumed
-        if op_id <= get_last_operation_id(&e) {
-            panic_with_error!(&e, PoolError::OperationIdAlreadyConsumed);
+    fn swap_batch_via_router(
+        e: Env,
+        operator: Address,
+        requests: Vec<(Address, u128)>,
+        swaps_chain: Vec<(Vec<Address>, BytesN<32>, Address)>,
+        out_min: i128,
+    ) -> i128 {
+        require_operator(&e, &operator);
+        if requests.is_empty() {
+            panic_with_error!(&e, PoolError::EmptyBatch);
+        }
+        if requests.len() > get_max_batch_size(&e) {
+            panic_with_error!(&e, PoolError::BatchTooLarge);
         }
 
-        let token_in_client = SorobanTokenClient::new(&e, &token_in);
-        token_in_client.transfer_from(
-            &e.current_contract_address(),
-            &proxy_wallet,
-            &e.current_contract_address(),
-            &amount_in,
+        // all requests should share the same pair to be swapped at once
+        let mut swap_requests: Vec<SwapRequest> = Vec::new(&e);
+        let mut amounts_in = Vec::new(&e);
+        let mut seen_operations = Map::new(&e);
+        let mut total_in: i128 = 0;
+        let mut max_slippage_bps: Option<u32> = None;
+        for (destination, op_id) in requests.iter() {
+            if seen_operations.contains_key(op_id) {
+                panic_with_error!(&e, PoolError::DuplicateBatchRequest);
+            }
+            seen_operations.set(op_id, ());
+
+            let swap_request = get_swap_request_by_id(&e, &destination, op_id);
+            if let Some(first) = swap_requests.first() {
+                if swap_request.token_in != first.token_in
+                    || swap_request.token_out != first.token_out
+                {
+                    panic_with_error!(&e, PoolError::BatchPairMismatch);
+                }
+            }
+            total_in = match total_in.checked_add(swap_request.amount_in) {
+                Some(v) => v,
+                None => panic_with_error!(&e, MathError::NumberOverflow),
+            };
+            // all requests get the same price, so the strictest limit applies to the batch
+            if let Some(bps) = swap_request.max_slippage_bps {
+                max_slippage_bps = Some(max_slippage_bps.map_or(bps, |v| v.min(bps)));
+            }
+            amounts_in.push_back(swap_request.amount_in);
+            swap_requests.push_back(swap_request);
+        }
+
+        let first = swap_requests.first().unwrap();
+        require_not_paused(
+            &e,
+            &[
+                PauseTarget::Swaps,
+                PauseTarget::Token(first.token_in.clone()),
+                PauseTarget::Token(first.token_out.clone()),
+            ],
         );
+        let venue = Venue::Router(get_swap_router(&e));
+        venue.validate(&e, &swaps_chain, &first.token_in, &first.token_out);
 
-        let operational_fee = get_operational_fee(&e, &token_in);
-        if operational_fee > 0 {
-            token_in_client.transfer(&e.current_contract_address(), &operator, &operational_fee);
+        let slippage_reference = slippage_reference(
+            &e,
+            &venue,
+            &swaps_chain,
+            &first.token_in,
+            &first.token_out,
+            total_in,
+            max_slippage_bps,
+        );
+        let amount_out = swap_verified(
+            &e,
+            &venue,
+            &swaps_chain,
+            &first.token_in,
+            &first.token_out,
+            total_in,
+            out_min,
+        );
+        require_within_slippage(&e, slippage_reference, amount_out);
+        require_oracle_bound(&e, &first.token_in, &first.token_out, total_in, amount_out);
+
+        let token_out_client = SorobanTokenClient::new(&e, &first.token_out);
+        let shares = split_pro_rata(&e, amount_out, &amounts_in, total_in);
+        for (swap_request, share) in swap_requests.iter().zip(shares.iter()) {
+            if share > 0 {
+                token_out_client.transfer(
+                    &e.current_contract_address(),
+                    &swap_request.recipient,
+                    &share,
+                );
+            }
+            set_swap_request_processed(
+                &e,
+                &swap_request.destination,
+                swap_request.clone(),
+                share,
+                None,
+            );
+            Events::new(&e).request_fulfilled(&swap_request, share);
         }
 
-        add_swap_request(
+        amount_out
+    }
+
+    fn fulfill_direct(e: Env, operator: Address, destination: Address, op_id: u128) -> i128 {
+        require_operator(&e, &operator);
+
+        // same asset is bought as deposited, so nothing to swap
+        let swap_request = get_swap_request_by_id(&e, &destination, op_id);
+        if swap_request.token_in != swap_request.token_out {
+            panic_with_error!(&e, PoolError::DirectDeliveryNotAllowed);
+        }
+        require_not_paused(
             &e,
-            &destination,
-            &SwapRequest {
-                tx_id,
-                op_id,
-                destination: destination.clone(),
-                token_in,
-                amount_in: amount_in - operational_fee,
-                token_out,
-            },
+            &[
+                PauseTarget::Swaps,
+                PauseTarget::Token(swap_request.token_in.clone()),
+            ],
         );
 
-        // todo: emit event
+        let amount_out = swap_request.amount_in;
+        SorobanTokenClient::new(&e, &swap_request.token_in).transfer(
+            &e.current_contract_address(),
+            &swap_request.recipient,
+            &amount_out,
+        );
+
+        set_swap_request_processed(&e, &destination, swap_request.clone(), amount_out, None);
+
+        Events::new(&e).request_fulfilled(&swap_request, amount_out);
+
+        amount_out
     }
 
-    fn swap_chained_via_router(
+    fn quote_request(
         e: Env,
-        operator: Address,
         destination: Address,
         op_id: u128,
         swaps_chain: Vec<(Vec<Address>, BytesN<32>, Address)>,
-        out_min: i128,
+    ) -> RequestQuote {
+        let swap_request = get_swap_request_by_id(&e, &destination, op_id);
+        let venue = Venue::Router(get_swap_router(&e));
+        venue.validate(
+            &e,
+            &swaps_chain,
+            &swap_request.token_in,
+            &swap_request.token_out,
+        );
+
+        let amount_in = swap_request.amount_in as u128;
+        let amount_out = venue.estimate(
+            &e,
+            &swaps_chain,
+            &swap_request.token_in,
+            &swap_request.token_out,
+            amount_in,
+        );
+        // spot is derived from the router estimates, so the impact holds for any pool type
+        let spot_out = venue.spot_amount_out(
+            &e,
+            &swaps_chain,
+            &swap_request.token_in,
+            &swap_request.token_out,
+            amount_in,
+        );
+        let price_impact_bps = if spot_out > amount_out {
+            match (spot_out - amount_out).checked_mul(BASIS_POINTS as u128) {
+                Some(v) => (v / spot_out) as u32,
+                None => panic_with_error!(&e, MathError::NumberOverflow),
+            }
+        } else {
+            0
+        };
+
+        RequestQuote {
+            amount_out: amount_out as i128,
+            fee: swap_request.fee,
+            price_impact_bps,
+        }
+    }
+
+    fn cancel_request(
+        e: Env,
+        user: Address,
+        destination: Address,
+        op_id: u128,
+        refund_to: Address,
+        refund_fee: bool,
     ) -> i128 {
-        operator.require_auth();
-        if operator != get_operator(&e) {
+        // both operator and admin are able to cancel request
+        user.require_auth();
+        migrate_legacy_operator(&e);
+        let access_control = AccessControl::new(&e);
+        let is_admin = access_control.has_role(&Role::Admin, &user);
+        if !is_admin && !access_control.has_role(&Role::Operator, &user) {
             panic_with_error!(&e, PoolError::UnauthorizedOperator);
         }
 
         let swap_request = get_swap_request_by_id(&e, &destination, op_id);
+        // operator is only able to return funds where they would go on expiry
+        if !is_admin && refund_to != refund_target(&e, &swap_request) {
+            panic_with_error!(&e, AccessControlError::UserNotAdmin);
+        }
 
-        // fulfill request
-        let amount_out = swap_with_router(
-            &e,
-            &get_swap_router(&e),
-            &swaps_chain,
-            &swap_request.token_in,
-            &(swap_request.amount_in as u128),
-            &(out_min as u128),
-        ) as i128;
+        let token_in_client = SorobanTokenClient::new(&e, &swap_request.token_in);
+        token_in_client.transfer(
+            &e.current_contract_address(),
+            &refund_to,
+            &swap_request.amount_in,
+        );
+
+        // operational fee is returned out of fees not yet withdrawn to treasury
+        let mut amount_refunded = swap_request.amount_in;
+        if refund_fee && swap_request.fee > 0 {
+            let accumulated_fees = get_accumulated_fees(&e, &swap_request.token_in);
+            if accumulated_fees < swap_request.fee {
+                panic_with_error!(&e, PoolError::NotEnoughAccumulatedFees);
+            }
+            set_accumulated_fees(
+                &e,
+                &swap_request.token_in,
+                &(accumulated_fees - swap_request.fee),
+            );
+            set_collected_fees(
+                &e,
+                &swap_request.token_in,
+                &(get_collected_fees(&e, &swap_request.token_in) - swap_request.fee),
+            );
+            token_in_client.transfer(&e.current_contract_address(), &refund_to, &swap_request.fee);
+            amount_refunded += swap_request.fee;
+        }
+
+        set_swap_request_refunded(&e, &destination, swap_request.clone());
+
+        Events::new(&e).request_refunded(&swap_request, &refund_to, amount_refunded);
 
-        // transfer swap result to destination
-        SorobanTokenClient::new(&e, &swap_request.token_out).transfer(
+        amount_refunded
+    }
+
+    fn reclaim_expired_request(e: Env, destination: Address, op_id: u128) -> i128 {
+        // anyone is able to return funds once request is expired
+        let swap_request = get_swap_request_by_id(&e, &destination, op_id);
+        if e.ledger().timestamp() < swap_request.expires_at {
+            panic_with_error!(&e, PoolError::RequestNotExpired);
+        }
+
+        let refund_to = refund_target(&e, &swap_request);
+        SorobanTokenClient::new(&e, &swap_request.token_in).transfer(
             &e.current_contract_address(),
-            &swap_request.destination,
-            &amount_out,
+            &refund_to,
+            &swap_request.amount_in,
         );
 
-        // mark swap as processed
-        set_swap_request_processed(&e, &destination, swap_request, amount_out);
+        set_swap_request_expired(&e, &destination, swap_request.clone());
 
-        // todo: emit event
+        Events::new(&e).request_expired(&swap_request, &refund_to, swap_request.amount_in);
 
-        amount_out
+        swap_request.amount_in
     }
 
     // public getters
+    fn get_admin(e: Env) -> Option<Address> {
+        AccessControl::new(&e).get_admin()
+    }
+
+    fn get_future_admin(e: Env) -> Option<Address> {
+        AccessControl::new(&e).get_future_admin()
+    }
+
+    // legacy operator is reported along with the role members until it's migrated
+    fn has_role(e: Env, role: Role, user: Address) -> bool {
+        if role == Role::Operator && get_legacy_operator(&e) == Some(user.clone()) {
+            return true;
+        }
+        AccessControl::new(&e).has_role(&role, &user)
+    }
+
+    fn get_role_members(e: Env, role: Role) -> Vec<Address> {
+        let mut members = AccessControl::new(&e).get_role_members(&role);
+        if role == Role::Operator {
+            if let Some(operator) = get_legacy_operator(&e) {
+                if !members.contains(&operator) {
+                    members.push_back(operator);
+                }
+            }
+        }
+        members
+    }
+
+    fn is_paused(e: Env, target: PauseTarget) -> bool {
+        is_paused(&e, &target)
+    }
+
     fn get_proxy_wallets(e: Env) -> Map<Address, Address> {
         get_proxy_wallets(&e)
     }
 
+    fn get_proxy_wallets_by_token(e: Env, token_out: Address) -> Vec<Address> {
+        get_proxy_wallets_by_token(&e, &token_out)
+    }
+
+    fn is_proxy_wallet_enabled(e: Env, proxy_wallet: Address) -> bool {
+        is_proxy_wallet_enabled(&e, &proxy_wallet)
+    }
+
+    fn get_proxy_wallet_info(e: Env, proxy_wallet: Address) -> Option<ProxyWalletInfo> {
+        get_proxy_wallet_info(&e, &proxy_wallet)
+    }
+
     fn get_last_operation_id(e: Env) -> u128 {
         get_last_operation_id(&e)
     }
 
+    fn get_operation_id_window(e: Env) -> u128 {
+        get_operation_id_window(&e)
+    }
+
+    fn get_max_batch_size(e: Env) -> u32 {
+        get_max_batch_size(&e)
+    }
+
+    fn get_request_ttl(e: Env) -> u64 {
+        get_request_ttl(&e)
+    }
+
+    fn get_refund_address(e: Env) -> Option<Address> {
+        get_refund_address(&e)
+    }
+
     fn get_requests(
         e: Env,
         destination: Address,
@@ -169,6 +1133,17 @@ impl PoolContractInterface for PoolContract {
                 request.token_out,
             ));
         }
+        // requests pending since before the upgrade are listed until they're migrated
+        for request in get_legacy_swap_requests(&e, &destination) {
+            result.push_back((
+                request.tx_id,
+                request.op_id,
+                request.destination,
+                request.token_in,
+                request.amount_in,
+                request.token_out,
+            ));
+        }
         result
     }
 
@@ -196,6 +1171,53 @@ impl PoolContractInterface for PoolContract {
         result
     }
 
+    fn get_completed_requests_details(
+        e: Env,
+        destination: Address,
+        page: u32,
+    ) -> Vec<CompletedSwapRequest> {
+        get_completed_swap_requests_page(&e, &destination, page)
+    }
+
+    fn get_request_by_op_id(e: Env, op_id: u128) -> (SwapRequest, RequestStatus, i128) {
+        get_swap_request_with_status(&e, op_id)
+    }
+
+    fn get_request_by_tx_id(e: Env, tx_id: BytesN<32>) -> (SwapRequest, RequestStatus, i128) {
+        match get_tx_operation_id(&e, &tx_id) {
+            Some(op_id) => get_swap_request_with_status(&e, op_id),
+            None => panic_with_error!(&e, StorageError::ValueMissing),
+        }
+    }
+
+    fn set_destination_preferences(
+        e: Env,
+        destination: Address,
+        token_out: Address,
+        max_slippage_bps: u32,
+        payout_address: Option<Address>,
+    ) {
+        destination.require_auth();
+        if max_slippage_bps > BASIS_POINTS {
+            panic_with_error!(&e, PoolError::InvalidSlippage);
+        }
+        let preferences = DestinationPreferences {
+            token_out,
+            max_slippage_bps,
+            payout_address: payout_address.unwrap_or(destination.clone()),
+        };
+        set_destination_preferences(&e, &destination, &Some(preferences));
+    }
+
+    fn remove_destination_preferences(e: Env, destination: Address) {
+        destination.require_auth();
+        set_destination_preferences(&e, &destination, &None);
+    }
+
+    fn get_destination_preferences(e: Env, destination: Address) -> Option<DestinationPreferences> {
+        get_destination_preferences(&e, &destination)
+    }
+
     fn get_destinations_last_page(e: Env) -> u32 {
         get_destinations_last_page(&e)
     }
@@ -204,24 +1226,128 @@ impl PoolContractInterface for PoolContract {
         get_destinations(&e, page)
     }
 
+    // flat part of the operational fee schedule
     fn get_operational_fee(e: Env, token: Address) -> i128 {
-        get_operational_fee(&e, &token)
+        get_operational_fee(&e, &token).flat
     }
 
     fn set_operational_fee(e: Env, operator: Address, token: Address, fee: i128) {
-        operator.require_auth();
-        if operator != get_operator(&e) {
-            panic_with_error!(&e, PoolError::UnauthorizedOperator);
+        require_operator(&e, &operator);
+        let schedule = FeeSchedule::from_flat(fee);
+        validate_fee_schedule(&e, &token, &schedule);
+        set_operational_fee(&e, &token, &schedule);
+    }
+
+    fn get_operational_fee_schedule(e: Env, token: Address) -> FeeSchedule {
+        get_operational_fee(&e, &token)
+    }
+
+    fn set_operational_fee_schedule(
+        e: Env,
+        operator: Address,
+        token: Address,
+        schedule: FeeSchedule,
+    ) {
+        require_operator(&e, &operator);
+        validate_fee_schedule(&e, &token, &schedule);
+        set_operational_fee(&e, &token, &schedule);
+    }
+
+    fn quote_operational_fee(e: Env, token: Address, amount: i128) -> i128 {
+        calculate_operational_fee(&e, &token, amount)
+    }
+
+    fn get_max_operational_fee(e: Env, token: Address) -> Option<i128> {
+        get_max_operational_fee(&e, &token)
+    }
+
+    fn set_max_operational_fee(e: Env, token: Address, max_fee: i128) {
+        let access_control = AccessControl::new(&e);
+        access_control.require_admin();
+        if max_fee < 0 {
+            panic_with_error!(&e, PoolError::NegativeFee);
         }
+        set_max_operational_fee(&e, &token, &max_fee);
+    }
 
-        set_operational_fee(&e, &token, &fee);
+    fn set_price_oracle(e: Env, oracle: Option<Address>) {
+        let access_control = AccessControl::new(&e);
+        access_control.require_admin();
+        set_price_oracle(&e, &oracle);
+    }
+
+    fn get_price_oracle(e: Env) -> Option<Address> {
+        get_price_oracle(&e)
+    }
+
+    fn set_max_oracle_deviation(e: Env, max_deviation_bps: u32) {
+        let access_control = AccessControl::new(&e);
+        access_control.require_admin();
+        if max_deviation_bps > BASIS_POINTS {
+            panic_with_error!(&e, PoolError::InvalidOracleDeviation);
+        }
+        set_max_oracle_deviation(&e, &max_deviation_bps);
+    }
+
+    fn get_max_oracle_deviation(e: Env) -> u32 {
+        get_max_oracle_deviation(&e)
+    }
+
+    fn set_max_oracle_price_age(e: Env, max_age: u64) {
+        let access_control = AccessControl::new(&e);
+        access_control.require_admin();
+        set_max_oracle_price_age(&e, &max_age);
+    }
+
+    fn get_max_oracle_price_age(e: Env) -> u64 {
+        get_max_oracle_price_age(&e)
+    }
+
+    fn set_fee_treasury(e: Env, treasury: Address) {
+        let access_control = AccessControl::new(&e);
+        access_control.require_admin();
+        set_fee_treasury(&e, &Some(treasury));
+    }
+
+    fn get_fee_treasury(e: Env) -> Option<Address> {
+        get_fee_treasury(&e)
+    }
+
+    fn get_accumulated_fees(e: Env, token: Address) -> i128 {
+        get_accumulated_fees(&e, &token)
+    }
+
+    fn get_collected_fees(e: Env, token: Address) -> i128 {
+        get_collected_fees(&e, &token)
+    }
+
+    fn withdraw_fees(e: Env, fee_manager: Address, token: Address) -> i128 {
+        fee_manager.require_auth();
+        AccessControl::new(&e).check_role(&Role::FeeManager, &fee_manager);
+
+        let treasury = match get_fee_treasury(&e) {
+            Some(v) => v,
+            None => panic_with_error!(&e, PoolError::FeeTreasuryNotSet),
+        };
+
+        let amount = get_accumulated_fees(&e, &token);
+        if amount > 0 {
+            set_accumulated_fees(&e, &token, &0);
+            SorobanTokenClient::new(&e, &token).transfer(
+                &e.current_contract_address(),
+                &treasury,
+                &amount,
+            );
+            Events::new(&e).fees_withdrawn(&token, amount, &treasury);
+        }
+        amount
     }
 }
 
 #[contractimpl]
 impl UpgradeableContract for PoolContract {
     fn version() -> u32 {
-        104
+        105
     }
 
     fn upgrade(e: Env, new_wasm_hash: BytesN<32>) {
@@ -11,4 +11,41 @@ pub enum PoolError {
     UnauthorizedOperator = 2302,
     UnauthorizedProxyWallet = 2303,
     TokenNotSupported = 2304,
+    RequestNotExpired = 2305,
+    Paused = 2306,
+    NegativeFee = 2307,
+    FeeAboveMaximum = 2308,
+    FeeExceedsAmount = 2309,
+    FeeTreasuryNotSet = 2310,
+    NotEnoughAccumulatedFees = 2311,
+    InvalidSwapChain = 2312,
+    SwapChainTokenInMismatch = 2313,
+    SwapChainTokenOutMismatch = 2314,
+    SwapChainBroken = 2315,
+    SwapChainTokensNotSorted = 2316,
+    SwapOutputBelowMinimum = 2317,
+    SwapOutputMismatch = 2318,
+    TxIdAlreadyConsumed = 2319,
+    BatchTooLarge = 2320,
+    EmptyBatch = 2321,
+    BatchPairMismatch = 2322,
+    DuplicateBatchRequest = 2323,
+    DirectDeliveryNotAllowed = 2324,
+    ProxyWalletDisabled = 2325,
+    TokenInNotAllowed = 2326,
+    DepositBelowMinimum = 2327,
+    DepositAboveMaximum = 2328,
+    InvalidDepositLimits = 2329,
+    InvalidSlippage = 2330,
+    SlippageExceeded = 2331,
+    OraclePriceUnavailable = 2332,
+    OutputDeviatesFromOracle = 2333,
+    InvalidOracleDeviation = 2334,
+    VenueNotFound = 2335,
+    VenuePairNotSupported = 2336,
+    NoRoutesProvided = 2337,
+    InvalidFeeRate = 2338,
+    InvalidFeeBounds = 2339,
+    InvalidBatchSize = 2340,
+    NoViableRoute = 2341,
 }
@@ -0,0 +1,284 @@
+use crate::constants::EVENTS_VERSION;
+use crate::storage::{PauseTarget, SwapRequest};
+use access_control::access::Role;
+use soroban_sdk::{contracttype, Address, BytesN, Env, IntoVal, Symbol, Val};
+
+// Request events are published with topics `(name, EVENTS_VERSION, destination)`,
+// admin events with topics `(name, EVENTS_VERSION)`.
+// EVENTS_VERSION must be bumped whenever the topics layout or any payload below changes,
+// so indexers are able to tell events emitted before and after an upgrade apart.
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RequestAddedEvent {
+    pub tx_id: BytesN<32>,
+    pub op_id: u128,
+    pub destination: Address,
+    pub token_in: Address,
+    pub amount_in: i128,
+    pub fee: i128,
+    pub token_out: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RequestFulfilledEvent {
+    pub tx_id: BytesN<32>,
+    pub op_id: u128,
+    pub destination: Address,
+    pub token_in: Address,
+    pub amount_in: i128,
+    pub fee: i128,
+    pub token_out: Address,
+    pub amount_out: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RequestRefundedEvent {
+    pub tx_id: BytesN<32>,
+    pub op_id: u128,
+    pub destination: Address,
+    pub token_in: Address,
+    pub amount_in: i128,
+    pub fee: i128,
+    pub refund_to: Address,
+    pub amount_refunded: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AdminTransferProposedEvent {
+    pub admin: Address,
+    pub future_admin: Address,
+    pub deadline: Option<u64>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AdminTransferAcceptedEvent {
+    pub previous_admin: Address,
+    pub admin: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AdminTransferCancelledEvent {
+    pub admin: Address,
+    pub future_admin: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RoleChangedEvent {
+    pub role: Role,
+    pub user: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PauseChangedEvent {
+    pub user: Address,
+    pub target: PauseTarget,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeesWithdrawnEvent {
+    pub token: Address,
+    pub amount: i128,
+    pub treasury: Address,
+}
+
+#[derive(Clone)]
+pub struct Events(Env);
+
+impl Events {
+    pub fn new(e: &Env) -> Events {
+        Events(e.clone())
+    }
+
+    fn publish<D>(&self, name: &str, destination: &Address, data: D)
+    where
+        D: IntoVal<Env, Val>,
+    {
+        self.0.events().publish(
+            (
+                Symbol::new(&self.0, name),
+                EVENTS_VERSION,
+                destination.clone(),
+            ),
+            data,
+        );
+    }
+
+    fn publish_admin<D>(&self, name: &str, data: D)
+    where
+        D: IntoVal<Env, Val>,
+    {
+        self.0
+            .events()
+            .publish((Symbol::new(&self.0, name), EVENTS_VERSION), data);
+    }
+
+    pub fn request_added(&self, request: &SwapRequest) {
+        self.publish(
+            "request_added",
+            &request.destination,
+            RequestAddedEvent {
+                tx_id: request.tx_id.clone(),
+                op_id: request.op_id,
+                destination: request.destination.clone(),
+                token_in: request.token_in.clone(),
+                amount_in: request.amount_in,
+                fee: request.fee,
+                token_out: request.token_out.clone(),
+            },
+        );
+    }
+
+    pub fn request_fulfilled(&self, request: &SwapRequest, amount_out: i128) {
+        self.publish(
+            "request_fulfilled",
+            &request.destination,
+            RequestFulfilledEvent {
+                tx_id: request.tx_id.clone(),
+                op_id: request.op_id,
+                destination: request.destination.clone(),
+                token_in: request.token_in.clone(),
+                amount_in: request.amount_in,
+                fee: request.fee,
+                token_out: request.token_out.clone(),
+                amount_out,
+            },
+        );
+    }
+
+    pub fn request_refunded(
+        &self,
+        request: &SwapRequest,
+        refund_to: &Address,
+        amount_refunded: i128,
+    ) {
+        self.publish_refund("request_refunded", request, refund_to, amount_refunded);
+    }
+
+    pub fn request_expired(
+        &self,
+        request: &SwapRequest,
+        refund_to: &Address,
+        amount_refunded: i128,
+    ) {
+        self.publish_refund("request_expired", request, refund_to, amount_refunded);
+    }
+
+    fn publish_refund(
+        &self,
+        name: &str,
+        request: &SwapRequest,
+        refund_to: &Address,
+        amount_refunded: i128,
+    ) {
+        self.publish(
+            name,
+            &request.destination,
+            RequestRefundedEvent {
+                tx_id: request.tx_id.clone(),
+                op_id: request.op_id,
+                destination: request.destination.clone(),
+                token_in: request.token_in.clone(),
+                amount_in: request.amount_in,
+                fee: request.fee,
+                refund_to: refund_to.clone(),
+                amount_refunded,
+            },
+        );
+    }
+
+    pub fn admin_transfer_proposed(
+        &self,
+        admin: &Address,
+        future_admin: &Address,
+        deadline: Option<u64>,
+    ) {
+        self.publish_admin(
+            "admin_transfer_proposed",
+            AdminTransferProposedEvent {
+                admin: admin.clone(),
+                future_admin: future_admin.clone(),
+                deadline,
+            },
+        );
+    }
+
+    pub fn admin_transfer_accepted(&self, previous_admin: &Address, admin: &Address) {
+        self.publish_admin(
+            "admin_transfer_accepted",
+            AdminTransferAcceptedEvent {
+                previous_admin: previous_admin.clone(),
+                admin: admin.clone(),
+            },
+        );
+    }
+
+    pub fn admin_transfer_cancelled(&self, admin: &Address, future_admin: &Address) {
+        self.publish_admin(
+            "admin_transfer_cancelled",
+            AdminTransferCancelledEvent {
+                admin: admin.clone(),
+                future_admin: future_admin.clone(),
+            },
+        );
+    }
+
+    pub fn role_granted(&self, role: Role, user: &Address) {
+        self.publish_admin(
+            "role_granted",
+            RoleChangedEvent {
+                role,
+                user: user.clone(),
+            },
+        );
+    }
+
+    pub fn role_revoked(&self, role: Role, user: &Address) {
+        self.publish_admin(
+            "role_revoked",
+            RoleChangedEvent {
+                role,
+                user: user.clone(),
+            },
+        );
+    }
+
+    pub fn paused(&self, user: &Address, target: &PauseTarget) {
+        self.publish_admin(
+            "paused",
+            PauseChangedEvent {
+                user: user.clone(),
+                target: target.clone(),
+            },
+        );
+    }
+
+    pub fn unpaused(&self, user: &Address, target: &PauseTarget) {
+        self.publish_admin(
+            "unpaused",
+            PauseChangedEvent {
+                user: user.clone(),
+                target: target.clone(),
+            },
+        );
+    }
+
+    pub fn fees_withdrawn(&self, token: &Address, amount: i128, treasury: &Address) {
+        self.publish_admin(
+            "fees_withdrawn",
+            FeesWithdrawnEvent {
+                token: token.clone(),
+                amount,
+                treasury: treasury.clone(),
+            },
+        );
+    }
+}
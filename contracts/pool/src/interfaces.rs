@@ -1,13 +1,56 @@
+use crate::storage::{
+    CompletedSwapRequest, DestinationPreferences, PauseTarget, ProxyWalletInfo, RequestDescriptor,
+    RequestQuote, RequestStatus, SwapRequest,
+};
+use crate::venue::{SwapRoute, Venue};
+use access_control::access::Role;
 use soroban_sdk::{Address, BytesN, Env, Map, Vec};
+use utils::fee::FeeSchedule;
 
 pub trait PoolContractInterface {
     fn set_admin(e: Env, admin: Address);
+    fn propose_admin(e: Env, new_admin: Address, deadline: Option<u64>);
+    fn accept_admin(e: Env);
+    fn cancel_admin_transfer(e: Env);
+    fn get_admin(e: Env) -> Option<Address>;
+    fn get_future_admin(e: Env) -> Option<Address>;
+
+    fn grant_role(e: Env, role: Role, user: Address);
+    fn revoke_role(e: Env, role: Role, user: Address);
+    fn has_role(e: Env, role: Role, user: Address) -> bool;
+    fn get_role_members(e: Env, role: Role) -> Vec<Address>;
+
+    // both admin and pauser are able to pause, while unpause is allowed for admin only
+    fn pause(e: Env, user: Address, target: PauseTarget);
+    fn unpause(e: Env, user: Address, target: PauseTarget);
+    fn is_paused(e: Env, target: PauseTarget) -> bool;
 
     fn set_operator(e: Env, operator: Address);
     fn add_proxy_wallet(e: Env, proxy_wallet: Address, token_out: Address);
+    fn remove_proxy_wallet(e: Env, proxy_wallet: Address);
+    fn set_proxy_wallet_info(e: Env, proxy_wallet: Address, info: ProxyWalletInfo);
+    fn set_proxy_wallet_enabled(e: Env, proxy_wallet: Address, enabled: bool);
     fn get_proxy_wallets(e: Env) -> Map<Address, Address>;
+    fn get_proxy_wallets_by_token(e: Env, token_out: Address) -> Vec<Address>;
+    fn is_proxy_wallet_enabled(e: Env, proxy_wallet: Address) -> bool;
+    fn get_proxy_wallet_info(e: Env, proxy_wallet: Address) -> Option<ProxyWalletInfo>;
 
+    fn register_venue(e: Env, venue_id: u32, venue: Venue);
+    fn remove_venue(e: Env, venue_id: u32);
+    fn get_venues(e: Env) -> Map<u32, Venue>;
     fn set_swap_router(e: Env, swap_router: Address);
+    fn set_request_ttl(e: Env, ttl: u64);
+    fn set_operation_id_window(e: Env, window: u128);
+    fn set_max_batch_size(e: Env, max_batch_size: u32);
+    fn set_refund_address(e: Env, refund_address: Option<Address>);
+    // moves up to limit pending requests stored by contract version 104 and earlier, returns number left.
+    // deposit wallet wasn't recorded by then, so admin provides the wallet refunds should go to
+    fn migrate_legacy_requests(
+        e: Env,
+        destination: Address,
+        refund_wallet: Address,
+        limit: u32,
+    ) -> u32;
 
     fn add_request(
         e: Env,
@@ -19,6 +62,7 @@ pub trait PoolContractInterface {
         token_in: Address,
         amount_in: i128,
     );
+    fn add_requests_batch(e: Env, operator: Address, requests: Vec<RequestDescriptor>);
 
     fn swap_chained_via_router(
         e: Env,
@@ -27,12 +71,59 @@ pub trait PoolContractInterface {
         op_id: u128,
         swaps_chain: Vec<(Vec<Address>, BytesN<32>, Address)>,
         out_min: i128,
-    ) -> i128; // getters
-               // get_swap by id
-               // get operator
-               // get swap router
+    ) -> i128;
+
+    fn swap_via_venue(
+        e: Env,
+        operator: Address,
+        destination: Address,
+        op_id: u128,
+        venue_id: u32,
+        swaps_chain: Vec<(Vec<Address>, BytesN<32>, Address)>,
+        out_min: i128,
+    ) -> i128;
+    fn swap_best_execution(
+        e: Env,
+        operator: Address,
+        destination: Address,
+        op_id: u128,
+        routes: Vec<SwapRoute>,
+        out_min: i128,
+    ) -> i128;
+    fn swap_batch_via_router(
+        e: Env,
+        operator: Address,
+        requests: Vec<(Address, u128)>,
+        swaps_chain: Vec<(Vec<Address>, BytesN<32>, Address)>,
+        out_min: i128,
+    ) -> i128;
+    fn fulfill_direct(e: Env, operator: Address, destination: Address, op_id: u128) -> i128;
+    fn quote_request(
+        e: Env,
+        destination: Address,
+        op_id: u128,
+        swaps_chain: Vec<(Vec<Address>, BytesN<32>, Address)>,
+    ) -> RequestQuote;
+    fn cancel_request(
+        e: Env,
+        user: Address,
+        destination: Address,
+        op_id: u128,
+        refund_to: Address,
+        refund_fee: bool,
+    ) -> i128;
+    fn reclaim_expired_request(e: Env, destination: Address, op_id: u128) -> i128;
+
+    // getters
+    // get_swap by id
+    // get operator
+    // get swap router
 
     fn get_last_operation_id(e: Env) -> u128;
+    fn get_operation_id_window(e: Env) -> u128;
+    fn get_max_batch_size(e: Env) -> u32;
+    fn get_request_ttl(e: Env) -> u64;
+    fn get_refund_address(e: Env) -> Option<Address>;
     fn get_requests(
         e: Env,
         destination: Address,
@@ -43,11 +134,51 @@ pub trait PoolContractInterface {
         destination: Address,
         page: u32,
     ) -> Vec<(BytesN<32>, u128, Address, Address, i128, Address, i128)>;
+    fn get_completed_requests_details(
+        e: Env,
+        destination: Address,
+        page: u32,
+    ) -> Vec<CompletedSwapRequest>;
+    fn get_request_by_op_id(e: Env, op_id: u128) -> (SwapRequest, RequestStatus, i128);
+    fn get_request_by_tx_id(e: Env, tx_id: BytesN<32>) -> (SwapRequest, RequestStatus, i128);
+    fn set_destination_preferences(
+        e: Env,
+        destination: Address,
+        token_out: Address,
+        max_slippage_bps: u32,
+        payout_address: Option<Address>,
+    );
+    fn remove_destination_preferences(e: Env, destination: Address);
+    fn get_destination_preferences(e: Env, destination: Address) -> Option<DestinationPreferences>;
     fn get_destinations_last_page(e: Env) -> u32;
     fn get_destinations(e: Env, page: u32) -> Vec<Address>;
 
     fn get_operational_fee(e: Env, token: Address) -> i128;
     fn set_operational_fee(e: Env, operator: Address, token: Address, fee: i128);
+    fn get_operational_fee_schedule(e: Env, token: Address) -> FeeSchedule;
+    fn set_operational_fee_schedule(
+        e: Env,
+        operator: Address,
+        token: Address,
+        schedule: FeeSchedule,
+    );
+    fn quote_operational_fee(e: Env, token: Address, amount: i128) -> i128;
+    fn get_max_operational_fee(e: Env, token: Address) -> Option<i128>;
+    fn set_max_operational_fee(e: Env, token: Address, max_fee: i128);
+
+    fn set_price_oracle(e: Env, oracle: Option<Address>);
+    fn get_price_oracle(e: Env) -> Option<Address>;
+    fn set_max_oracle_deviation(e: Env, max_deviation_bps: u32);
+    fn get_max_oracle_deviation(e: Env) -> u32;
+    // oracle price older than max age in seconds is treated as unavailable
+    fn set_max_oracle_price_age(e: Env, max_age: u64);
+    fn get_max_oracle_price_age(e: Env) -> u64;
+
+    fn set_fee_treasury(e: Env, treasury: Address);
+    fn get_fee_treasury(e: Env) -> Option<Address>;
+    fn get_accumulated_fees(e: Env, token: Address) -> i128;
+    fn get_collected_fees(e: Env, token: Address) -> i128;
+    fn withdraw_fees(e: Env, fee_manager: Address, token: Address) -> i128;
 }
 
 pub trait UpgradeableContract {
@@ -1,11 +1,15 @@
 #![no_std]
+#![allow(clippy::too_many_arguments)]
 
 mod constants;
 mod contract;
 mod errors;
+mod events;
 mod interfaces;
+mod oracle;
 mod storage;
 mod swap_router;
 mod test;
+mod venue;
 
 pub use contract::{PoolContract, PoolContractClient};
@@ -0,0 +1,89 @@
+use crate::errors::PoolError;
+use soroban_sdk::token::TokenClient as SorobanTokenClient;
+use soroban_sdk::{contractclient, contracttype, panic_with_error, Address, Env, Symbol};
+use utils::constant::BASIS_POINTS;
+use utils::math_errors::MathError;
+
+// subset of SEP-40 price feed interface
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Asset {
+    Stellar(Address),
+    Other(Symbol),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceData {
+    pub price: i128,
+    pub timestamp: u64,
+}
+
+#[allow(dead_code)]
+#[contractclient(name = "PriceOracleClient")]
+pub trait PriceOracle {
+    fn decimals(e: Env) -> u32;
+    fn x_last_price(e: Env, base_asset: Asset, quote_asset: Asset) -> Option<PriceData>;
+}
+
+// swap output should not be worse than oracle-implied value by more than max deviation.
+// better execution than oracle price is never rejected, stale price is never trusted
+pub(crate) fn check_oracle_deviation(
+    e: &Env,
+    oracle: &Address,
+    max_deviation_bps: u32,
+    max_price_age: u64,
+    token_in: &Address,
+    token_out: &Address,
+    amount_in: i128,
+    amount_out: i128,
+) {
+    let client = PriceOracleClient::new(e, oracle);
+    let price = match client.x_last_price(
+        &Asset::Stellar(token_in.clone()),
+        &Asset::Stellar(token_out.clone()),
+    ) {
+        Some(data)
+            if data.price > 0
+                && e.ledger().timestamp().saturating_sub(data.timestamp) <= max_price_age =>
+        {
+            data.price
+        }
+        _ => panic_with_error!(e, PoolError::OraclePriceUnavailable),
+    };
+
+    // price is quoted per whole token, so amounts are brought to the same units first:
+    // expected_out = amount_in * price * 10^decimals_out / (10^oracle_decimals * 10^decimals_in)
+    let decimals_in = SorobanTokenClient::new(e, token_in).decimals();
+    let decimals_out = SorobanTokenClient::new(e, token_out).decimals();
+    let scale_down = client.decimals() as i64 + decimals_in as i64 - decimals_out as i64;
+    let value = match amount_in.checked_mul(price) {
+        Some(v) => v,
+        None => panic_with_error!(e, MathError::NumberOverflow),
+    };
+    let expected_out = if scale_down >= 0 {
+        value / pow10(e, scale_down)
+    } else {
+        match value.checked_mul(pow10(e, -scale_down)) {
+            Some(v) => v,
+            None => panic_with_error!(e, MathError::NumberOverflow),
+        }
+    };
+    let min_out = match expected_out.checked_mul((BASIS_POINTS - max_deviation_bps) as i128) {
+        Some(v) => v / BASIS_POINTS as i128,
+        None => panic_with_error!(e, MathError::NumberOverflow),
+    };
+    if amount_out < min_out {
+        panic_with_error!(e, PoolError::OutputDeviatesFromOracle);
+    }
+}
+
+fn pow10(e: &Env, exp: i64) -> i128 {
+    match u32::try_from(exp)
+        .ok()
+        .and_then(|exp| 10_i128.checked_pow(exp))
+    {
+        Some(v) => v,
+        None => panic_with_error!(e, MathError::NumberOverflow),
+    }
+}
@@ -1,8 +1,13 @@
-use crate::constants::{COMPLETED_REQUESTS_PAGE_SIZE, DESTINATIONS_PAGE_SIZE};
+use crate::constants::{
+    COMPLETED_REQUESTS_PAGE_SIZE, DEFAULT_MAX_BATCH_SIZE, DEFAULT_MAX_ORACLE_DEVIATION_BPS,
+    DEFAULT_MAX_ORACLE_PRICE_AGE, DEFAULT_REQUEST_TTL, DESTINATIONS_PAGE_SIZE,
+};
 use crate::errors::PoolError;
+use crate::venue::Venue;
 use paste::paste;
-use soroban_sdk::{contracttype, panic_with_error, Address, BytesN, Env, Map, Vec};
+use soroban_sdk::{contracttype, panic_with_error, Address, BytesN, Env, Map, String, Symbol, Vec};
 use utils::bump::{bump_instance, bump_persistent};
+use utils::fee::FeeSchedule;
 use utils::storage_errors::StorageError;
 use utils::{
     generate_instance_storage_getter, generate_instance_storage_getter_and_setter,
@@ -14,20 +19,118 @@ use utils::{
 #[contracttype]
 enum DataKey {
     ProxyWallets,
+    // single operator of contract version 104 and earlier, superseded by operator role
     Operator,
+    ProxyWalletDisabled(Address),
+    ProxyWalletInfo(Address),
+    DestinationPreferences(Address),
+    // flat fee of contract version 104 and earlier, superseded by fee schedule
     OperationalFee(Address),
+    OperationalFeeSchedule(Address),
+    MaxOperationalFee(Address),
+    FeeTreasury,
+    PriceOracle,
+    MaxOracleDeviation,
+    MaxOraclePriceAge,
+    AccumulatedFees(Address),
+    CollectedFees(Address),
     SwapRouter,
+    Venues,
+    // pending requests of contract version 104 and earlier, drained by migration
     SwapRequests(Address),
+    SwapRequest(u128),
+    SwapRequestsIndex(Address),
+    SwapRequestLinks(u128),
+    ProcessedSwapRequest(u128),
+    TxOperationId(BytesN<32>),
     LastOperationId,
+    OperationIdWindow,
+    OperationIdFloor,
+    MaxBatchSize,
+    // pages written before the completed request layout was extended, read-only
     CompletedSwapRequests(Address, u32),
+    CompletedSwapRequestsV2(Address, u32),
     CompletedSwapRequestLastPage(Address),
     DestinationsList(u32),
     DestinationsLastPage,
+    RequestTtl,
+    RefundAddress,
+    Paused(PauseTarget),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PauseTarget {
+    Intake,
+    Swaps,
+    Token(Address),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProxyWalletInfo {
+    pub provider_id: Symbol,
+    pub label: String,
+    pub allowed_tokens_in: Vec<Address>,
+    // outputs destination is able to choose besides wallet's default token
+    pub allowed_tokens_out: Vec<Address>,
+    pub min_amount: i128,
+    pub max_amount: Option<i128>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DestinationPreferences {
+    pub token_out: Address,
+    pub max_slippage_bps: u32,
+    // destination itself unless alternate payout address is set
+    pub payout_address: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RequestQuote {
+    pub amount_out: i128,
+    // operational fee deducted from the deposit at intake
+    pub fee: i128,
+    // output loss against spot price after pools fees
+    pub price_impact_bps: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RequestDescriptor {
+    pub proxy_wallet: Address,
+    pub tx_id: BytesN<32>,
+    pub op_id: u128,
+    pub destination: Address,
+    pub token_in: Address,
+    pub amount_in: i128,
 }
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct SwapRequest {
+    pub tx_id: BytesN<32>,
+    pub op_id: u128,
+    pub destination: Address,
+    pub token_in: Address,
+    pub amount_in: i128,
+    pub fee: i128,
+    pub token_out: Address,
+    // address receiving the swap result, destination unless overridden by its preferences
+    pub recipient: Address,
+    pub max_slippage_bps: Option<u32>,
+    pub proxy_wallet: Address,
+    pub created_at: u64,
+    pub created_ledger: u32,
+    pub expires_at: u64,
+}
+
+// pending request layout of contract version 104 and earlier
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LegacySwapRequest {
     pub tx_id: BytesN<32>,
     pub op_id: u128,
     pub destination: Address,
@@ -36,6 +139,32 @@ pub struct SwapRequest {
     pub token_out: Address,
 }
 
+// pending requests of a destination are linked in order of arrival, so adding or removing one
+// touches a fixed number of entries regardless of how many requests are pending
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct SwapRequestsIndex {
+    first: Option<u128>,
+    last: Option<u128>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct SwapRequestLinks {
+    prev: Option<u128>,
+    next: Option<u128>,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum RequestStatus {
+    Pending = 0,
+    Completed = 1,
+    Refunded = 2,
+    Expired = 3,
+}
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct CompletedSwapRequest {
@@ -46,9 +175,24 @@ pub struct CompletedSwapRequest {
     pub amount_in: i128,
     pub token_out: Address,
     pub amount_out: i128,
+    pub status: RequestStatus,
+    // registered venue the swap was executed on, none for configured router and direct delivery
+    pub venue_id: Option<u32>,
+}
+
+// completed request layout of contract version 104 and earlier
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LegacyCompletedSwapRequest {
+    pub tx_id: BytesN<32>,
+    pub op_id: u128,
+    pub destination: Address,
+    pub token_in: Address,
+    pub amount_in: i128,
+    pub token_out: Address,
+    pub amount_out: i128,
 }
 
-generate_instance_storage_getter_and_setter!(operator, DataKey::Operator, Address);
 generate_instance_storage_getter_and_setter!(swap_router, DataKey::SwapRouter, Address);
 generate_instance_storage_getter_and_setter!(proxy_wallet, DataKey::ProxyWallets, Address);
 generate_instance_storage_getter_and_setter_with_default!(
@@ -57,6 +201,62 @@ generate_instance_storage_getter_and_setter_with_default!(
     u128,
     0
 );
+generate_instance_storage_getter_and_setter_with_default!(
+    operation_id_window,
+    DataKey::OperationIdWindow,
+    u128,
+    0
+);
+// last operation id when the window was first enabled, requests consumed before that
+// may not have individual entries, so nothing at or below it is accepted
+generate_instance_storage_getter_and_setter_with_default!(
+    operation_id_floor,
+    DataKey::OperationIdFloor,
+    Option<u128>,
+    None
+);
+generate_instance_storage_getter_and_setter_with_default!(
+    max_batch_size,
+    DataKey::MaxBatchSize,
+    u32,
+    DEFAULT_MAX_BATCH_SIZE
+);
+generate_instance_storage_getter_and_setter_with_default!(
+    request_ttl,
+    DataKey::RequestTtl,
+    u64,
+    DEFAULT_REQUEST_TTL
+);
+generate_instance_storage_getter_and_setter_with_default!(
+    refund_address,
+    DataKey::RefundAddress,
+    Option<Address>,
+    None
+);
+generate_instance_storage_getter_and_setter_with_default!(
+    price_oracle,
+    DataKey::PriceOracle,
+    Option<Address>,
+    None
+);
+generate_instance_storage_getter_and_setter_with_default!(
+    max_oracle_deviation,
+    DataKey::MaxOracleDeviation,
+    u32,
+    DEFAULT_MAX_ORACLE_DEVIATION_BPS
+);
+generate_instance_storage_getter_and_setter_with_default!(
+    max_oracle_price_age,
+    DataKey::MaxOraclePriceAge,
+    u64,
+    DEFAULT_MAX_ORACLE_PRICE_AGE
+);
+generate_instance_storage_getter_and_setter_with_default!(
+    fee_treasury,
+    DataKey::FeeTreasury,
+    Option<Address>,
+    None
+);
 generate_instance_storage_getter_and_setter_with_default!(
     destinations_last_page,
     DataKey::DestinationsLastPage,
@@ -83,37 +283,245 @@ fn set_proxy_wallets(e: &Env, value: &Map<Address, Address>) {
 
 // should we keep it in persistent storage rather than instance?
 pub fn add_proxy_wallet(e: &Env, proxy_wallet: &Address, token_out: &Address) {
+    // multiple wallets may sell the same token
     let mut wallets = get_proxy_wallets(e);
+    wallets.set(proxy_wallet.clone(), token_out.clone());
+    set_proxy_wallets(e, &wallets);
+}
 
-    for (k, v) in wallets.iter() {
-        if &v == token_out {
-            wallets.remove(k);
-        }
+pub fn remove_proxy_wallet(e: &Env, proxy_wallet: &Address) {
+    let mut wallets = get_proxy_wallets(e);
+    if !wallets.contains_key(proxy_wallet.clone()) {
+        panic_with_error!(e, PoolError::UnauthorizedProxyWallet);
     }
-    wallets.set(proxy_wallet.clone(), token_out.clone());
+    wallets.remove(proxy_wallet.clone());
     set_proxy_wallets(e, &wallets);
+    set_proxy_wallet_enabled(e, proxy_wallet, true);
+    e.storage()
+        .persistent()
+        .remove(&DataKey::ProxyWalletInfo(proxy_wallet.clone()));
 }
 
-pub fn get_active_swap_requests(e: &Env, destination: &Address) -> Vec<SwapRequest> {
-    let key = DataKey::SwapRequests(destination.clone());
+pub fn get_proxy_wallet_info(e: &Env, proxy_wallet: &Address) -> Option<ProxyWalletInfo> {
+    let key = DataKey::ProxyWalletInfo(proxy_wallet.clone());
+    match e.storage().persistent().get(&key) {
+        Some(v) => {
+            bump_persistent(e, &key);
+            Some(v)
+        }
+        None => None,
+    }
+}
+
+pub fn get_destination_preferences(
+    e: &Env,
+    destination: &Address,
+) -> Option<DestinationPreferences> {
+    let key = DataKey::DestinationPreferences(destination.clone());
+    match e.storage().persistent().get(&key) {
+        Some(v) => {
+            bump_persistent(e, &key);
+            Some(v)
+        }
+        None => None,
+    }
+}
+
+pub fn set_destination_preferences(
+    e: &Env,
+    destination: &Address,
+    value: &Option<DestinationPreferences>,
+) {
+    let key = DataKey::DestinationPreferences(destination.clone());
+    match value {
+        Some(v) => {
+            e.storage().persistent().set(&key, v);
+            bump_persistent(e, &key);
+        }
+        None => e.storage().persistent().remove(&key),
+    }
+}
+
+pub fn set_proxy_wallet_info(e: &Env, proxy_wallet: &Address, value: &ProxyWalletInfo) {
+    let key = DataKey::ProxyWalletInfo(proxy_wallet.clone());
+    e.storage().persistent().set(&key, value);
+    bump_persistent(e, &key);
+}
+
+pub fn get_proxy_wallets_by_token(e: &Env, token_out: &Address) -> Vec<Address> {
+    let mut result = Vec::new(e);
+    for (wallet, wallet_token_out) in get_proxy_wallets(e).iter() {
+        if &wallet_token_out == token_out {
+            result.push_back(wallet);
+        }
+    }
+    result
+}
+
+pub fn is_proxy_wallet_enabled(e: &Env, proxy_wallet: &Address) -> bool {
+    bump_instance(e);
+    !e.storage()
+        .instance()
+        .has(&DataKey::ProxyWalletDisabled(proxy_wallet.clone()))
+}
+
+pub fn set_proxy_wallet_enabled(e: &Env, proxy_wallet: &Address, enabled: bool) {
+    bump_instance(e);
+    let key = DataKey::ProxyWalletDisabled(proxy_wallet.clone());
+    if enabled {
+        e.storage().instance().remove(&key);
+    } else {
+        e.storage().instance().set(&key, &true);
+    }
+}
+
+pub fn get_venues(e: &Env) -> Map<u32, Venue> {
+    let key = DataKey::Venues;
     match e.storage().persistent().get(&key) {
         Some(v) => {
             bump_persistent(e, &key);
             v
         }
-        None => Vec::new(e),
+        None => Map::new(e),
+    }
+}
+
+fn set_venues(e: &Env, value: &Map<u32, Venue>) {
+    let key = DataKey::Venues;
+    e.storage().persistent().set(&key, value);
+    bump_persistent(e, &key);
+}
+
+pub fn get_venue(e: &Env, venue_id: u32) -> Venue {
+    match get_venues(e).get(venue_id) {
+        Some(venue) => venue,
+        None => panic_with_error!(e, PoolError::VenueNotFound),
+    }
+}
+
+pub fn set_venue(e: &Env, venue_id: u32, venue: &Venue) {
+    let mut venues = get_venues(e);
+    venues.set(venue_id, venue.clone());
+    set_venues(e, &venues);
+}
+
+pub fn remove_venue(e: &Env, venue_id: u32) {
+    let mut venues = get_venues(e);
+    if venues.remove(venue_id).is_none() {
+        panic_with_error!(e, PoolError::VenueNotFound);
+    }
+    set_venues(e, &venues);
+}
+
+pub fn get_legacy_operator(e: &Env) -> Option<Address> {
+    bump_instance(e);
+    e.storage().instance().get(&DataKey::Operator)
+}
+
+// legacy operator is removed once read, so it's carried over to the role only once
+pub fn take_legacy_operator(e: &Env) -> Option<Address> {
+    let operator = get_legacy_operator(e);
+    if operator.is_some() {
+        e.storage().instance().remove(&DataKey::Operator);
+    }
+    operator
+}
+
+// active requests are stored one per entry, destination keeps ends of their list
+fn get_swap_requests_index(e: &Env, destination: &Address) -> SwapRequestsIndex {
+    let key = DataKey::SwapRequestsIndex(destination.clone());
+    match e.storage().persistent().get(&key) {
+        Some(v) => {
+            bump_persistent(e, &key);
+            v
+        }
+        None => SwapRequestsIndex {
+            first: None,
+            last: None,
+        },
+    }
+}
+
+fn set_swap_requests_index(e: &Env, destination: &Address, value: &SwapRequestsIndex) {
+    let key = DataKey::SwapRequestsIndex(destination.clone());
+    e.storage().persistent().set(&key, value);
+    bump_persistent(e, &key);
+}
+
+fn get_swap_request_links(e: &Env, op_id: u128) -> SwapRequestLinks {
+    let key = DataKey::SwapRequestLinks(op_id);
+    match e.storage().persistent().get(&key) {
+        Some(v) => {
+            bump_persistent(e, &key);
+            v
+        }
+        None => panic_with_error!(e, StorageError::ValueMissing),
+    }
+}
+
+fn set_swap_request_links(e: &Env, op_id: u128, value: &SwapRequestLinks) {
+    let key = DataKey::SwapRequestLinks(op_id);
+    e.storage().persistent().set(&key, value);
+    bump_persistent(e, &key);
+}
+
+fn get_swap_request(e: &Env, op_id: u128) -> Option<SwapRequest> {
+    let key = DataKey::SwapRequest(op_id);
+    match e.storage().persistent().get(&key) {
+        Some(v) => {
+            bump_persistent(e, &key);
+            Some(v)
+        }
+        None => None,
+    }
+}
+
+fn set_swap_request(e: &Env, value: &SwapRequest) {
+    let key = DataKey::SwapRequest(value.op_id);
+    e.storage().persistent().set(&key, value);
+    bump_persistent(e, &key);
+}
+
+pub fn get_active_swap_requests(e: &Env, destination: &Address) -> Vec<SwapRequest> {
+    let mut requests = Vec::new(e);
+    let mut next = get_swap_requests_index(e, destination).first;
+    while let Some(op_id) = next {
+        match get_swap_request(e, op_id) {
+            Some(request) => requests.push_back(request),
+            None => panic_with_error!(e, StorageError::ValueMissing),
+        }
+        next = get_swap_request_links(e, op_id).next;
     }
+    requests
 }
 
 pub fn is_new_destination(e: &Env, destination: &Address) -> bool {
+    let key = DataKey::SwapRequestsIndex(destination.clone());
+    let legacy_key = DataKey::SwapRequests(destination.clone());
+    !e.storage().persistent().has(&key) && !e.storage().persistent().has(&legacy_key)
+}
+
+pub fn get_legacy_swap_requests(e: &Env, destination: &Address) -> Vec<LegacySwapRequest> {
     let key = DataKey::SwapRequests(destination.clone());
-    !e.storage().persistent().has(&key)
+    match e.storage().persistent().get(&key) {
+        Some(v) => {
+            bump_persistent(e, &key);
+            v
+        }
+        None => Vec::new(e),
+    }
 }
 
-pub fn set_active_swap_requests(e: &Env, destination: &Address, value: &Vec<SwapRequest>) {
+// legacy entry is dropped once drained, index entry keeps destination known from then on
+pub fn set_legacy_swap_requests(e: &Env, destination: &Address, value: &Vec<LegacySwapRequest>) {
     let key = DataKey::SwapRequests(destination.clone());
-    e.storage().persistent().set(&key, value);
-    bump_persistent(e, &key);
+    if value.is_empty() {
+        set_swap_requests_index(e, destination, &get_swap_requests_index(e, destination));
+        e.storage().persistent().remove(&key);
+    } else {
+        e.storage().persistent().set(&key, value);
+        bump_persistent(e, &key);
+    }
 }
 
 pub fn add_swap_request(e: &Env, destination: &Address, value: &SwapRequest) {
@@ -121,20 +529,118 @@ pub fn add_swap_request(e: &Env, destination: &Address, value: &SwapRequest) {
         add_destination(e, destination);
     }
 
-    let mut requests = get_active_swap_requests(e, destination);
-    set_last_operation_id(e, &value.op_id);
-    requests.push_back(value.clone());
-    set_active_swap_requests(e, destination, &requests);
+    // operation ids may arrive out of order, keep the highest one seen
+    if value.op_id > get_last_operation_id(e) {
+        set_last_operation_id(e, &value.op_id);
+    }
+    set_swap_request(e, value);
+    set_tx_operation_id(e, &value.tx_id, value.op_id);
+
+    // request is appended to the end of destination's list
+    let mut index = get_swap_requests_index(e, destination);
+    match index.last {
+        Some(last) => {
+            let mut links = get_swap_request_links(e, last);
+            links.next = Some(value.op_id);
+            set_swap_request_links(e, last, &links);
+        }
+        None => index.first = Some(value.op_id),
+    }
+    set_swap_request_links(
+        e,
+        value.op_id,
+        &SwapRequestLinks {
+            prev: index.last,
+            next: None,
+        },
+    );
+    index.last = Some(value.op_id);
+    set_swap_requests_index(e, destination, &index);
 }
 
-pub fn get_swap_request_by_id(e: &Env, destination: &Address, op_id: u128) -> SwapRequest {
-    let requests = get_active_swap_requests(e, destination);
-    for request in requests {
-        if request.op_id == op_id {
-            return request;
+// unlinks request from destination's list, neighbours are linked to each other instead
+fn remove_from_swap_requests_index(e: &Env, destination: &Address, op_id: u128) {
+    let links = get_swap_request_links(e, op_id);
+    let mut index = get_swap_requests_index(e, destination);
+    let initial_index = index.clone();
+    match links.prev {
+        Some(prev) => {
+            let mut prev_links = get_swap_request_links(e, prev);
+            prev_links.next = links.next;
+            set_swap_request_links(e, prev, &prev_links);
         }
+        None => index.first = links.next,
+    }
+    match links.next {
+        Some(next) => {
+            let mut next_links = get_swap_request_links(e, next);
+            next_links.prev = links.prev;
+            set_swap_request_links(e, next, &next_links);
+        }
+        None => index.last = links.prev,
+    }
+    if index != initial_index {
+        set_swap_requests_index(e, destination, &index);
+    }
+    e.storage()
+        .persistent()
+        .remove(&DataKey::SwapRequestLinks(op_id));
+}
+
+fn set_tx_operation_id(e: &Env, tx_id: &BytesN<32>, op_id: u128) {
+    let key = DataKey::TxOperationId(tx_id.clone());
+    e.storage().persistent().set(&key, &op_id);
+    bump_persistent(e, &key);
+}
+
+// operation id is consumed once request is stored, either pending or processed
+pub fn is_operation_id_consumed(e: &Env, op_id: u128) -> bool {
+    e.storage().persistent().has(&DataKey::SwapRequest(op_id))
+        || e.storage()
+            .persistent()
+            .has(&DataKey::ProcessedSwapRequest(op_id))
+}
+
+// tx id entries are persistent and bumped to max ttl, archived entry can't be read without
+// restoring it first, so a consumed tx id is never treated as a new one
+pub fn is_tx_id_consumed(e: &Env, tx_id: &BytesN<32>) -> bool {
+    e.storage()
+        .persistent()
+        .has(&DataKey::TxOperationId(tx_id.clone()))
+}
+
+pub fn get_tx_operation_id(e: &Env, tx_id: &BytesN<32>) -> Option<u128> {
+    let key = DataKey::TxOperationId(tx_id.clone());
+    match e.storage().persistent().get(&key) {
+        Some(v) => {
+            bump_persistent(e, &key);
+            Some(v)
+        }
+        None => None,
+    }
+}
+
+// request with its status and amount out, regardless of destination
+pub fn get_swap_request_with_status(e: &Env, op_id: u128) -> (SwapRequest, RequestStatus, i128) {
+    if let Some(request) = get_swap_request(e, op_id) {
+        return (request, RequestStatus::Pending, 0);
+    }
+
+    let key = DataKey::ProcessedSwapRequest(op_id);
+    match e.storage().persistent().get(&key) {
+        Some(v) => {
+            bump_persistent(e, &key);
+            v
+        }
+        None => panic_with_error!(e, StorageError::ValueMissing),
+    }
+}
+
+pub fn get_swap_request_by_id(e: &Env, destination: &Address, op_id: u128) -> SwapRequest {
+    match get_swap_request(e, op_id) {
+        Some(request) if &request.destination == destination => request,
+        _ => panic_with_error!(e, StorageError::ValueMissing),
     }
-    panic_with_error!(e, StorageError::ValueMissing)
 }
 
 pub fn get_completed_swap_requests_last_page(e: &Env, destination: &Address) -> u32 {
@@ -154,19 +660,50 @@ pub fn set_completed_swap_requests_last_page(e: &Env, destination: &Address, val
     bump_persistent(e, &key);
 }
 
-pub fn get_completed_swap_requests_page(
+fn get_legacy_completed_swap_requests_page(
     e: &Env,
     destination: &Address,
     page: u32,
-) -> Vec<CompletedSwapRequest> {
+) -> Option<Vec<LegacyCompletedSwapRequest>> {
     let key = DataKey::CompletedSwapRequests(destination.clone(), page);
     match e.storage().persistent().get(&key) {
         Some(v) => {
             bump_persistent(e, &key);
-            v
+            Some(v)
+        }
+        None => None,
+    }
+}
+
+// pages keep the layout they were written with, legacy ones are converted on read
+pub fn get_completed_swap_requests_page(
+    e: &Env,
+    destination: &Address,
+    page: u32,
+) -> Vec<CompletedSwapRequest> {
+    let key = DataKey::CompletedSwapRequestsV2(destination.clone(), page);
+    if let Some(v) = e.storage().persistent().get(&key) {
+        bump_persistent(e, &key);
+        return v;
+    }
+
+    let mut requests = Vec::new(e);
+    if let Some(legacy) = get_legacy_completed_swap_requests_page(e, destination, page) {
+        for request in legacy {
+            requests.push_back(CompletedSwapRequest {
+                tx_id: request.tx_id,
+                op_id: request.op_id,
+                destination: request.destination,
+                token_in: request.token_in,
+                amount_in: request.amount_in,
+                token_out: request.token_out,
+                amount_out: request.amount_out,
+                status: RequestStatus::Completed,
+                venue_id: None,
+            });
         }
-        None => Vec::new(e),
     }
+    requests
 }
 
 pub fn set_completed_swap_requests_page(
@@ -175,13 +712,20 @@ pub fn set_completed_swap_requests_page(
     page: u32,
     value: &Vec<CompletedSwapRequest>,
 ) {
-    let key = DataKey::CompletedSwapRequests(destination.clone(), page);
+    let key = DataKey::CompletedSwapRequestsV2(destination.clone(), page);
     e.storage().persistent().set(&key, value);
     bump_persistent(e, &key);
 }
 
 pub fn add_completed_swap_request(e: &Env, destination: &Address, value: CompletedSwapRequest) {
-    let last_page = get_completed_swap_requests_last_page(e, destination);
+    let mut last_page = get_completed_swap_requests_last_page(e, destination);
+    // partially filled legacy page is left as is, new records start on the next page
+    let legacy_key = DataKey::CompletedSwapRequests(destination.clone(), last_page);
+    if e.storage().persistent().has(&legacy_key) {
+        last_page += 1;
+        set_completed_swap_requests_last_page(e, destination, last_page);
+    }
+
     let mut requests = get_completed_swap_requests_page(e, destination, last_page);
     requests.push_back(value);
     set_completed_swap_requests_page(e, destination, last_page, &requests);
@@ -190,34 +734,79 @@ pub fn add_completed_swap_request(e: &Env, destination: &Address, value: Complet
     }
 }
 
+fn archive_swap_request(
+    e: &Env,
+    destination: &Address,
+    swap_request: SwapRequest,
+    amount_out: i128,
+    status: RequestStatus,
+    venue_id: Option<u32>,
+) {
+    remove_from_swap_requests_index(e, destination, swap_request.op_id);
+
+    // processed request is moved aside to keep it available for global lookup
+    e.storage()
+        .persistent()
+        .remove(&DataKey::SwapRequest(swap_request.op_id));
+    let key = DataKey::ProcessedSwapRequest(swap_request.op_id);
+    e.storage()
+        .persistent()
+        .set(&key, &(swap_request.clone(), status, amount_out));
+    bump_persistent(e, &key);
+    add_completed_swap_request(
+        e,
+        destination,
+        CompletedSwapRequest {
+            tx_id: swap_request.tx_id,
+            op_id: swap_request.op_id,
+            destination: swap_request.destination,
+            token_in: swap_request.token_in,
+            amount_in: swap_request.amount_in,
+            token_out: swap_request.token_out,
+            amount_out,
+            status,
+            venue_id,
+        },
+    );
+}
+
 pub fn set_swap_request_processed(
     e: &Env,
     destination: &Address,
     swap_request: SwapRequest,
     amount_out: i128,
+    venue_id: Option<u32>,
 ) {
-    let mut requests = get_active_swap_requests(e, destination);
-    let index = requests.last_index_of(swap_request.clone());
-    match index {
-        Some(index) => {
-            requests.remove(index);
-            set_active_swap_requests(e, destination, &requests);
-            add_completed_swap_request(
-                e,
-                destination,
-                CompletedSwapRequest {
-                    tx_id: swap_request.tx_id,
-                    op_id: swap_request.op_id,
-                    destination: swap_request.destination,
-                    token_in: swap_request.token_in,
-                    amount_in: swap_request.amount_in,
-                    token_out: swap_request.token_out,
-                    amount_out,
-                },
-            );
-        }
-        None => panic_with_error!(e, StorageError::ValueMissing),
-    }
+    archive_swap_request(
+        e,
+        destination,
+        swap_request,
+        amount_out,
+        RequestStatus::Completed,
+        venue_id,
+    );
+}
+
+pub fn set_swap_request_refunded(e: &Env, destination: &Address, swap_request: SwapRequest) {
+    archive_swap_request(
+        e,
+        destination,
+        swap_request,
+        0,
+        RequestStatus::Refunded,
+        None,
+    );
+}
+
+pub fn set_swap_request_expired(e: &Env, destination: &Address, swap_request: SwapRequest) {
+    archive_swap_request(
+        e,
+        destination,
+        swap_request,
+        0,
+        RequestStatus::Expired,
+        None,
+    );
 }
 
 pub fn get_destinations(e: &Env, page: u32) -> Vec<Address> {
@@ -247,20 +836,103 @@ pub fn add_destination(e: &Env, destination: &Address) {
     }
 }
 
-// operational fee per input token
-pub fn get_operational_fee(e: &Env, token: &Address) -> i128 {
-    let key = DataKey::OperationalFee(token.clone());
+// operational fee per input token, flat fee set before schedules were introduced is used as is
+pub fn get_operational_fee(e: &Env, token: &Address) -> FeeSchedule {
+    let key = DataKey::OperationalFeeSchedule(token.clone());
+    if let Some(v) = e.storage().persistent().get(&key) {
+        bump_persistent(e, &key);
+        return v;
+    }
+
+    let legacy_key = DataKey::OperationalFee(token.clone());
+    match e.storage().persistent().get(&legacy_key) {
+        Some(v) => {
+            bump_persistent(e, &legacy_key);
+            FeeSchedule::from_flat(v)
+        }
+        None => panic_with_error!(e, PoolError::TokenNotSupported),
+    }
+}
+
+pub fn set_operational_fee(e: &Env, token: &Address, value: &FeeSchedule) {
+    let key = DataKey::OperationalFeeSchedule(token.clone());
+    e.storage().persistent().set(&key, value);
+    bump_persistent(e, &key);
+    e.storage()
+        .persistent()
+        .remove(&DataKey::OperationalFee(token.clone()));
+}
+
+// admin-configured ceiling of operational fee per input token
+pub fn get_max_operational_fee(e: &Env, token: &Address) -> Option<i128> {
+    let key = DataKey::MaxOperationalFee(token.clone());
+    match e.storage().persistent().get(&key) {
+        Some(v) => {
+            bump_persistent(e, &key);
+            Some(v)
+        }
+        None => None,
+    }
+}
+
+pub fn set_max_operational_fee(e: &Env, token: &Address, value: &i128) {
+    let key = DataKey::MaxOperationalFee(token.clone());
+    e.storage().persistent().set(&key, value);
+    bump_persistent(e, &key);
+}
+
+// fees held by the contract until withdrawn to treasury
+pub fn get_accumulated_fees(e: &Env, token: &Address) -> i128 {
+    let key = DataKey::AccumulatedFees(token.clone());
     match e.storage().persistent().get(&key) {
         Some(v) => {
             bump_persistent(e, &key);
             v
         }
-        None => panic_with_error!(e, PoolError::TokenNotSupported),
+        None => 0,
     }
 }
 
-pub fn set_operational_fee(e: &Env, token: &Address, value: &i128) {
-    let key = DataKey::OperationalFee(token.clone());
+pub fn set_accumulated_fees(e: &Env, token: &Address, value: &i128) {
+    let key = DataKey::AccumulatedFees(token.clone());
     e.storage().persistent().set(&key, value);
     bump_persistent(e, &key);
 }
+
+// fees collected over the contract lifetime, net of refunds
+pub fn get_collected_fees(e: &Env, token: &Address) -> i128 {
+    let key = DataKey::CollectedFees(token.clone());
+    match e.storage().persistent().get(&key) {
+        Some(v) => {
+            bump_persistent(e, &key);
+            v
+        }
+        None => 0,
+    }
+}
+
+pub fn set_collected_fees(e: &Env, token: &Address, value: &i128) {
+    let key = DataKey::CollectedFees(token.clone());
+    e.storage().persistent().set(&key, value);
+    bump_persistent(e, &key);
+}
+
+pub fn add_collected_fee(e: &Env, token: &Address, fee: i128) {
+    set_accumulated_fees(e, token, &(get_accumulated_fees(e, token) + fee));
+    set_collected_fees(e, token, &(get_collected_fees(e, token) + fee));
+}
+
+pub fn is_paused(e: &Env, target: &PauseTarget) -> bool {
+    bump_instance(e);
+    e.storage().instance().has(&DataKey::Paused(target.clone()))
+}
+
+pub fn set_paused(e: &Env, target: &PauseTarget, paused: bool) {
+    bump_instance(e);
+    let key = DataKey::Paused(target.clone());
+    if paused {
+        e.storage().instance().set(&key, &true);
+    } else {
+        e.storage().instance().remove(&key);
+    }
+}
@@ -1,30 +1,136 @@
+use crate::constants::SPOT_PROBE_DIVISOR;
+use crate::errors::PoolError;
 use soroban_sdk::auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation};
-use soroban_sdk::{vec, Address, BytesN, Env, IntoVal, Symbol, Vec};
+use soroban_sdk::{panic_with_error, vec, Address, BytesN, Env, IntoVal, Symbol, Vec};
+use utils::token_utils::check_vec_ordered;
 
+#[allow(clippy::module_inception)]
 pub mod swap_router {
     soroban_sdk::contractimport!(file = "../../wasm/soroban_liquidity_pool_router_contract.wasm");
 }
 
-pub(crate) fn swap_with_router(
+// every hop should swap previous hop output within the pool it belongs to,
+// starting with the request input token and ending with the request output token
+pub(crate) fn check_swaps_chain(
+    swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+    token_in: &Address,
+    token_out: &Address,
+) -> Result<(), PoolError> {
+    if swaps_chain.is_empty() {
+        return Err(PoolError::InvalidSwapChain);
+    }
+
+    let mut hop_token_in = token_in.clone();
+    for (i, (tokens, _pool_index, hop_token_out)) in swaps_chain.iter().enumerate() {
+        if tokens.len() < 2 {
+            return Err(PoolError::InvalidSwapChain);
+        }
+        if !check_vec_ordered(&tokens) {
+            return Err(PoolError::SwapChainTokensNotSorted);
+        }
+        if !tokens.contains(&hop_token_in) {
+            if i == 0 {
+                return Err(PoolError::SwapChainTokenInMismatch);
+            }
+            return Err(PoolError::SwapChainBroken);
+        }
+        if hop_token_out == hop_token_in || !tokens.contains(&hop_token_out) {
+            return Err(PoolError::SwapChainBroken);
+        }
+        hop_token_in = hop_token_out;
+    }
+
+    if &hop_token_in != token_out {
+        return Err(PoolError::SwapChainTokenOutMismatch);
+    }
+    Ok(())
+}
+
+pub(crate) fn validate_swaps_chain(
+    e: &Env,
+    swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+    token_in: &Address,
+    token_out: &Address,
+) {
+    if let Err(error) = check_swaps_chain(swaps_chain, token_in, token_out) {
+        panic_with_error!(e, error);
+    }
+}
+
+// expected chain output, estimated hop by hop with the router
+pub(crate) fn estimate_swaps_chain(
     e: &Env,
     router: &Address,
     swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
     token_in: &Address,
-    in_amount: &u128,
-    out_min: &u128,
+    in_amount: u128,
 ) -> u128 {
+    let client = swap_router::Client::new(e, router);
+    let mut amount = in_amount;
+    let mut hop_token_in = token_in.clone();
+    for (tokens, pool_index, hop_token_out) in swaps_chain.iter() {
+        amount = client.estimate_swap(&tokens, &hop_token_in, &hop_token_out, &pool_index, &amount);
+        hop_token_in = hop_token_out;
+    }
+    amount
+}
+
+// same as estimate_swaps_chain, but invalid chain or failing pool gives none instead of reverting
+pub(crate) fn try_estimate_swaps_chain(
+    e: &Env,
+    router: &Address,
+    swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+    token_in: &Address,
+    token_out: &Address,
+    in_amount: u128,
+) -> Option<u128> {
+    check_swaps_chain(swaps_chain, token_in, token_out).ok()?;
+    let client = swap_router::Client::new(e, router);
+    let mut amount = in_amount;
+    let mut hop_token_in = token_in.clone();
+    for (tokens, pool_index, hop_token_out) in swaps_chain.iter() {
+        amount = client
+            .try_estimate_swap(&tokens, &hop_token_in, &hop_token_out, &pool_index, &amount)
+            .ok()?
+            .ok()?;
+        hop_token_in = hop_token_out;
+    }
+    Some(amount)
+}
+
+// output of in_amount at the marginal price, extrapolated from the estimate for a small probe.
+// estimates are used instead of pools reserves as the marginal price depends on the pool type.
+// amounts too small to be split are referenced by their own estimate.
+// none if estimate fails or result overflows
+pub(crate) fn probe_amount_out(
+    in_amount: u128,
+    estimate: impl Fn(u128) -> Option<u128>,
+) -> Option<u128> {
+    let probe = match in_amount / SPOT_PROBE_DIVISOR {
+        0 => in_amount,
+        v => v,
+    };
+    if probe == 0 {
+        return Some(0);
+    }
+    estimate(probe)?.checked_mul(in_amount).map(|v| v / probe)
+}
+
+// allows `spender` contract to take `amount` of `token` from the current contract
+// within the next sub-invocation
+pub(crate) fn authorize_transfer(e: &Env, token: &Address, spender: &Address, amount: i128) {
     e.authorize_as_current_contract(vec![
         &e,
         InvokerContractAuthEntry::Contract(SubContractInvocation {
             context: ContractContext {
-                contract: token_in.clone(),
+                contract: token.clone(),
                 fn_name: Symbol::new(e, "transfer"),
                 args: Vec::from_array(
                     e,
                     [
                         e.current_contract_address().to_val(),
-                        router.clone().to_val(),
-                        (*in_amount as i128).into_val(e),
+                        spender.clone().to_val(),
+                        amount.into_val(e),
                     ],
                 )
                 .into_val(e),
@@ -32,6 +138,17 @@ pub(crate) fn swap_with_router(
             sub_invocations: Vec::new(e),
         }),
     ]);
+}
+
+pub(crate) fn swap_with_router(
+    e: &Env,
+    router: &Address,
+    swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+    token_in: &Address,
+    in_amount: &u128,
+    out_min: &u128,
+) -> u128 {
+    authorize_transfer(e, token_in, router, *in_amount as i128);
 
     swap_router::Client::new(e, router).swap_chained(
         &e.current_contract_address(),
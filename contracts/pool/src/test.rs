@@ -1,20 +1,35 @@
 #![cfg(test)]
 use super::*;
+use crate::constants::{MAX_BATCH_SIZE_LIMIT, TX_MAX_WRITE_LEDGER_ENTRIES};
+use crate::errors::PoolError;
+use crate::events::{
+    AdminTransferAcceptedEvent, AdminTransferProposedEvent, RequestAddedEvent,
+    RequestFulfilledEvent, RequestRefundedEvent,
+};
+use crate::storage::{
+    CompletedSwapRequest, DestinationPreferences, LegacyCompletedSwapRequest, LegacySwapRequest,
+    PauseTarget, ProxyWalletInfo, RequestDescriptor, RequestQuote, RequestStatus,
+};
 use crate::swap_router::swap_router;
+use crate::venue::{liquidity_pool, SwapRoute, Venue};
+use access_control::access::Role;
 use soroban_sdk::testutils::arbitrary::std;
 use soroban_sdk::testutils::{
-    Address as _, AuthorizedFunction, AuthorizedInvocation, MockAuth, MockAuthInvoke,
+    Address as _, AuthorizedFunction, AuthorizedInvocation, Events as _, Ledger as _, MockAuth,
+    MockAuthInvoke,
 };
 use soroban_sdk::token::{
     StellarAssetClient as SorobanTokenAdminClient, TokenClient as SorobanTokenClient,
 };
-use soroban_sdk::{vec, Address, BytesN, Env, IntoVal, Map, Symbol, Vec};
+use soroban_sdk::{vec, Address, BytesN, Env, Error, IntoVal, Map, String, Symbol, Val, Vec};
+use utils::fee::FeeSchedule;
+use utils::math_errors::MathError;
 
 fn create_token_contract<'a>(e: &Env, admin: &Address) -> SorobanTokenClient<'a> {
     SorobanTokenClient::new(e, &e.register_stellar_asset_contract(admin.clone()))
 }
 
-fn deploy_liqpool_router_contract<'a>(e: &Env) -> swap_router::Client {
+fn deploy_liqpool_router_contract<'a>(e: &Env) -> swap_router::Client<'a> {
     swap_router::Client::new(e, &e.register_contract_wasm(None, swap_router::WASM))
 }
 
@@ -35,7 +50,7 @@ fn install_stableswap_liq_pool_hash(e: &Env) -> BytesN<32> {
     e.deployer().upload_contract_wasm(WASM)
 }
 
-fn deploy_plane_contract<'a>(e: &Env) -> Address {
+fn deploy_plane_contract(e: &Env) -> Address {
     soroban_sdk::contractimport!(file = "../../wasm/soroban_liquidity_pool_plane_contract.wasm");
     Client::new(e, &e.register_contract_wasm(None, WASM)).address
 }
@@ -46,15 +61,343 @@ mod swap_calculator {
     );
 }
 
-fn deploy_swap_calculator_contract<'a>(e: &Env) -> swap_calculator::Client {
+fn deploy_swap_calculator_contract<'a>(e: &Env) -> swap_calculator::Client<'a> {
     swap_calculator::Client::new(e, &e.register_contract_wasm(None, swap_calculator::WASM))
 }
 
+// router reporting arbitrary swap result while sending configured amount
+mod mock_router {
+    use soroban_sdk::token::TokenClient;
+    use soroban_sdk::{contract, contractimpl, symbol_short, Address, BytesN, Env, Vec};
+
+    #[contract]
+    pub struct MockRouter;
+
+    #[contractimpl]
+    impl MockRouter {
+        pub fn init(e: Env, token_out: Address, actual_out: u128, reported_out: u128) {
+            e.storage()
+                .instance()
+                .set(&symbol_short!("token"), &token_out);
+            e.storage()
+                .instance()
+                .set(&symbol_short!("actual"), &actual_out);
+            e.storage()
+                .instance()
+                .set(&symbol_short!("reported"), &reported_out);
+        }
+
+        pub fn swap_chained(
+            e: Env,
+            user: Address,
+            _swaps_chain: Vec<(Vec<Address>, BytesN<32>, Address)>,
+            token_in: Address,
+            in_amount: u128,
+            _out_min: u128,
+        ) -> u128 {
+            user.require_auth();
+            let token_out: Address = e.storage().instance().get(&symbol_short!("token")).unwrap();
+            let actual_out: u128 = e
+                .storage()
+                .instance()
+                .get(&symbol_short!("actual"))
+                .unwrap();
+            TokenClient::new(&e, &token_in).transfer(
+                &user,
+                &e.current_contract_address(),
+                &(in_amount as i128),
+            );
+            TokenClient::new(&e, &token_out).transfer(
+                &e.current_contract_address(),
+                &user,
+                &(actual_out as i128),
+            );
+            e.storage()
+                .instance()
+                .get(&symbol_short!("reported"))
+                .unwrap()
+        }
+    }
+}
+
+fn deploy_mock_router<'a>(
+    e: &Env,
+    token_out: &Address,
+    actual_out: u128,
+    reported_out: u128,
+) -> mock_router::MockRouterClient<'a> {
+    let router = mock_router::MockRouterClient::new(
+        e,
+        &e.register_contract(None, mock_router::MockRouter {}),
+    );
+    router.init(token_out, &actual_out, &reported_out);
+    SorobanTokenAdminClient::new(e, token_out).mint(&router.address, &10000);
+    router
+}
+
+// oracle returning configured price for any pair
+mod mock_oracle {
+    use crate::oracle::{Asset, PriceData};
+    use soroban_sdk::{contract, contractimpl, symbol_short, Env};
+
+    #[contract]
+    pub struct MockOracle;
+
+    #[contractimpl]
+    impl MockOracle {
+        pub fn set_price(e: Env, price: Option<i128>) {
+            e.storage().instance().set(&symbol_short!("price"), &price);
+        }
+
+        // price is reported as fresh unless timestamp is set
+        pub fn set_timestamp(e: Env, timestamp: u64) {
+            e.storage().instance().set(&symbol_short!("ts"), &timestamp);
+        }
+
+        pub fn set_decimals(e: Env, decimals: u32) {
+            e.storage()
+                .instance()
+                .set(&symbol_short!("decimals"), &decimals);
+        }
+
+        pub fn decimals(e: Env) -> u32 {
+            e.storage()
+                .instance()
+                .get(&symbol_short!("decimals"))
+                .unwrap_or(7)
+        }
+
+        pub fn x_last_price(e: Env, _base_asset: Asset, _quote_asset: Asset) -> Option<PriceData> {
+            let price: Option<i128> = e.storage().instance().get(&symbol_short!("price")).unwrap();
+            let timestamp = e
+                .storage()
+                .instance()
+                .get(&symbol_short!("ts"))
+                .unwrap_or(e.ledger().timestamp());
+            price.map(|price| PriceData { price, timestamp })
+        }
+    }
+}
+
+fn deploy_mock_oracle<'a>(e: &Env, price: Option<i128>) -> mock_oracle::MockOracleClient<'a> {
+    let oracle = mock_oracle::MockOracleClient::new(
+        e,
+        &e.register_contract(None, mock_oracle::MockOracle {}),
+    );
+    oracle.set_price(&price);
+    oracle
+}
+
+// adapter swapping any pair at fixed 1:2 rate
+mod mock_adapter {
+    use soroban_sdk::token::TokenClient;
+    use soroban_sdk::{contract, contractimpl, Address, Env};
+
+    #[contract]
+    pub struct MockAdapter;
+
+    #[contractimpl]
+    impl MockAdapter {
+        pub fn estimate_swap(
+            _e: Env,
+            _token_in: Address,
+            _token_out: Address,
+            in_amount: u128,
+        ) -> u128 {
+            in_amount / 2
+        }
+
+        pub fn swap(
+            e: Env,
+            user: Address,
+            token_in: Address,
+            token_out: Address,
+            in_amount: u128,
+            _out_min: u128,
+        ) -> u128 {
+            user.require_auth();
+            TokenClient::new(&e, &token_in).transfer(
+                &user,
+                &e.current_contract_address(),
+                &(in_amount as i128),
+            );
+            TokenClient::new(&e, &token_out).transfer(
+                &e.current_contract_address(),
+                &user,
+                &(in_amount as i128 / 2),
+            );
+            in_amount / 2
+        }
+    }
+}
+
+fn deploy_mock_adapter(e: &Env, token_out: &Address) -> Address {
+    let adapter = e.register_contract(None, mock_adapter::MockAdapter {});
+    SorobanTokenAdminClient::new(e, token_out).mint(&adapter, &10000);
+    adapter
+}
+
+// storage keys written by contract version 104, used to emulate state left before an upgrade
+mod legacy {
+    use soroban_sdk::{contracttype, Address};
+
+    #[contracttype]
+    pub enum DataKey {
+        Operator,
+        OperationalFee(Address),
+        SwapRequests(Address),
+        LastOperationId,
+        CompletedSwapRequests(Address, u32),
+        CompletedSwapRequestLastPage(Address),
+        DestinationsList(u32),
+    }
+}
+
 fn deploy_swap_pool<'a>(e: &Env) -> PoolContractClient<'a> {
     let pool = PoolContractClient::new(e, &e.register_contract(None, PoolContract {}));
     pool
 }
 
+fn assert_contract_error<T, I>(result: Result<T, Result<Error, I>>, code: u32) {
+    match result {
+        Err(Ok(error)) => assert_eq!(error, Error::from_contract_error(code)),
+        _ => panic!("expected contract error #{}", code),
+    }
+}
+
+fn last_event(e: &Env) -> Vec<(Address, Vec<Val>, Val)> {
+    let events = e.events().all();
+    events.slice(events.len() - 1..)
+}
+
+// checks the last call was authorized by the given address only
+fn assert_auth(e: &Env, address: &Address, contract: &Address, fn_name: &str, args: Vec<Val>) {
+    assert_eq!(
+        e.auths(),
+        std::vec![(
+            address.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    contract.clone(),
+                    Symbol::new(e, fn_name),
+                    args
+                )),
+                sub_invocations: std::vec![],
+            }
+        )]
+    );
+}
+
+struct Setup<'a> {
+    e: Env,
+    admin: Address,
+    operator: Address,
+    proxy_wallet: Address,
+    token_in: SorobanTokenClient<'a>,
+    token_out: SorobanTokenClient<'a>,
+    swaps_chain: Vec<(Vec<Address>, BytesN<32>, Address)>,
+    router: swap_router::Client<'a>,
+    liquidity_pool: Address,
+    swap_pool: PoolContractClient<'a>,
+}
+
+impl Setup<'_> {
+    // single pool router setup with the swap pool configured to sell token_out for token_in
+    fn default() -> Self {
+        let e = Env::default();
+        e.budget().reset_unlimited();
+        e.mock_all_auths();
+
+        let admin = Address::generate(&e);
+        let operator = Address::generate(&e);
+        let proxy_wallet = Address::generate(&e);
+
+        let mut tokens = std::vec![
+            create_token_contract(&e, &admin).address,
+            create_token_contract(&e, &admin).address,
+        ];
+        tokens.sort();
+        let token_in = SorobanTokenClient::new(&e, &tokens[0]);
+        let token_out = SorobanTokenClient::new(&e, &tokens[1]);
+        let pair = Vec::from_array(&e, [tokens[0].clone(), tokens[1].clone()]);
+
+        let plane = deploy_plane_contract(&e);
+        let swap_router = deploy_swap_calculator_contract(&e);
+        swap_router.init_admin(&admin);
+        swap_router.set_pools_plane(&admin, &plane);
+        let router = deploy_liqpool_router_contract(&e);
+        router.init_admin(&admin);
+        router.set_pool_hash(&install_liq_pool_hash(&e));
+        router.set_stableswap_pool_hash(&install_stableswap_liq_pool_hash(&e));
+        router.set_token_hash(&install_token_wasm(&e));
+        router.set_reward_token(&token_in.address);
+        router.set_pools_plane(&admin, &plane);
+        router.set_swap_router(&admin, &swap_router.address);
+
+        let (pool_index, liquidity_pool) = router.init_standard_pool(&admin, &pair, &30);
+        SorobanTokenAdminClient::new(&e, &tokens[0]).mint(&admin, &10000);
+        SorobanTokenAdminClient::new(&e, &tokens[1]).mint(&admin, &10000);
+        router.deposit(
+            &admin,
+            &pair,
+            &pool_index,
+            &Vec::from_array(&e, [10000, 10000]),
+            &0,
+        );
+        let swaps_chain = Vec::from_array(&e, [(pair, pool_index, tokens[1].clone())]);
+
+        let swap_pool = deploy_swap_pool(&e);
+        swap_pool.set_admin(&admin);
+        swap_pool.set_operator(&operator);
+        swap_pool.set_swap_router(&router.address);
+        swap_pool.add_proxy_wallet(&proxy_wallet, &token_out.address);
+        swap_pool.set_operational_fee(&operator, &token_in.address, &0);
+
+        SorobanTokenAdminClient::new(&e, &tokens[0]).mint(&proxy_wallet, &10000);
+        token_in.approve(&proxy_wallet, &swap_pool.address, &i128::MAX, &9999);
+
+        Setup {
+            e,
+            admin,
+            operator,
+            proxy_wallet,
+            token_in,
+            token_out,
+            swaps_chain,
+            router,
+            liquidity_pool,
+            swap_pool,
+        }
+    }
+
+    // deepens the swap pool, so that amounts are large enough to probe spot price with
+    fn add_liquidity(&self, amount: i128) {
+        SorobanTokenAdminClient::new(&self.e, &self.token_in.address).mint(&self.admin, &amount);
+        SorobanTokenAdminClient::new(&self.e, &self.token_out.address).mint(&self.admin, &amount);
+        liquidity_pool::Client::new(&self.e, &self.liquidity_pool).deposit(
+            &self.admin,
+            &vec![&self.e, amount as u128, amount as u128],
+            &0,
+        );
+        SorobanTokenAdminClient::new(&self.e, &self.token_in.address)
+            .mint(&self.proxy_wallet, &amount);
+    }
+
+    fn add_request(&self, op_id: u128, destination: &Address, amount_in: i128) {
+        let mut tx_id = [0; 32];
+        tx_id[..16].copy_from_slice(&op_id.to_be_bytes());
+        self.swap_pool.add_request(
+            &self.operator,
+            &self.proxy_wallet,
+            &BytesN::from_array(&self.e, &tx_id),
+            &op_id,
+            destination,
+            &self.token_in.address,
+            &amount_in,
+        );
+    }
+}
+
 #[test]
 fn test_chained_swap() {
     let e = Env::default();
@@ -213,7 +556,8 @@ fn test_chained_swap() {
             ),]
         )
     );
-    assert_eq!(token1.balance(&operator), 42);
+    assert_eq!(token1.balance(&operator), 0);
+    assert_eq!(swap_pool.get_accumulated_fees(&token_in), 42);
     assert_eq!(swap_pool.get_completed_requests_last_page(&destination), 0);
     assert_eq!(
         swap_pool.get_completed_requests(&destination, &0),
@@ -387,7 +731,7 @@ fn test_duplicate_destination() {
     swap_pool.add_request(
         &operator,
         &proxy_wallet,
-        &BytesN::from_array(&e, &[0; 32]),
+        &BytesN::from_array(&e, &[1; 32]),
         &operation_id,
         &destination,
         &token_in,
@@ -421,19 +765,29 @@ fn test_overwrite_wallet() {
     swap_pool.add_proxy_wallet(&proxy_wallet1, &token1);
     assert_eq!(
         swap_pool.get_proxy_wallets(),
-        Map::from_array(&e, [(proxy_wallet1, token1.clone())])
+        Map::from_array(&e, [(proxy_wallet1.clone(), token1.clone())])
     );
+    // wallets selling the same token coexist
     swap_pool.add_proxy_wallet(&proxy_wallet2, &token1);
     assert_eq!(
         swap_pool.get_proxy_wallets(),
-        Map::from_array(&e, [(proxy_wallet2.clone(), token1.clone())])
+        Map::from_array(
+            &e,
+            [
+                (proxy_wallet1.clone(), token1.clone()),
+                (proxy_wallet2.clone(), token1.clone())
+            ]
+        )
     );
     swap_pool.add_proxy_wallet(&proxy_wallet3, &token2);
+    // adding the same wallet again overwrites its token
+    swap_pool.add_proxy_wallet(&proxy_wallet1, &token2);
     assert_eq!(
         swap_pool.get_proxy_wallets(),
         Map::from_array(
             &e,
             [
+                (proxy_wallet1, token2.clone()),
                 (proxy_wallet2, token1.clone()),
                 (proxy_wallet3, token2.clone())
             ]
@@ -512,3 +866,2741 @@ fn test_unsupported_token_in() {
         &100,
     );
 }
+
+#[test]
+fn test_request_events() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    setup
+        .swap_pool
+        .set_operational_fee(&setup.operator, &setup.token_in.address, &10);
+
+    let mut tx_id = [0; 32];
+    tx_id[15] = 1;
+    setup.add_request(1, &destination, 110);
+    assert_eq!(
+        last_event(e),
+        vec![
+            e,
+            (
+                setup.swap_pool.address.clone(),
+                (Symbol::new(e, "request_added"), 1_u32, destination.clone()).into_val(e),
+                RequestAddedEvent {
+                    tx_id: BytesN::from_array(e, &tx_id),
+                    op_id: 1,
+                    destination: destination.clone(),
+                    token_in: setup.token_in.address.clone(),
+                    amount_in: 100,
+                    fee: 10,
+                    token_out: setup.token_out.address.clone(),
+                }
+                .into_val(e),
+            )
+        ]
+    );
+
+    setup.swap_pool.swap_chained_via_router(
+        &setup.operator,
+        &destination,
+        &1,
+        &setup.swaps_chain,
+        &90,
+    );
+    assert_eq!(
+        last_event(e),
+        vec![
+            e,
+            (
+                setup.swap_pool.address.clone(),
+                (
+                    Symbol::new(e, "request_fulfilled"),
+                    1_u32,
+                    destination.clone()
+                )
+                    .into_val(e),
+                RequestFulfilledEvent {
+                    tx_id: BytesN::from_array(e, &tx_id),
+                    op_id: 1,
+                    destination: destination.clone(),
+                    token_in: setup.token_in.address.clone(),
+                    amount_in: 100,
+                    fee: 10,
+                    token_out: setup.token_out.address.clone(),
+                    amount_out: 98,
+                }
+                .into_val(e),
+            )
+        ]
+    );
+}
+
+#[test]
+fn test_cancel_request() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    let refund_to = setup.proxy_wallet.clone();
+    setup
+        .swap_pool
+        .set_operational_fee(&setup.operator, &setup.token_in.address, &10);
+
+    setup.add_request(1, &destination, 110);
+    assert_eq!(
+        setup
+            .swap_pool
+            .get_accumulated_fees(&setup.token_in.address),
+        10
+    );
+
+    assert_eq!(
+        setup
+            .swap_pool
+            .cancel_request(&setup.operator, &destination, &1, &refund_to, &true),
+        110
+    );
+    assert_eq!(setup.token_in.balance(&refund_to), 10000);
+    assert_eq!(
+        setup
+            .swap_pool
+            .get_accumulated_fees(&setup.token_in.address),
+        0
+    );
+    assert_eq!(
+        setup.swap_pool.get_collected_fees(&setup.token_in.address),
+        0
+    );
+    assert_eq!(setup.token_in.balance(&setup.swap_pool.address), 0);
+
+    let mut tx_id = [0; 32];
+    tx_id[15] = 1;
+    assert_eq!(
+        last_event(e),
+        vec![
+            e,
+            (
+                setup.swap_pool.address.clone(),
+                (
+                    Symbol::new(e, "request_refunded"),
+                    1_u32,
+                    destination.clone()
+                )
+                    .into_val(e),
+                RequestRefundedEvent {
+                    tx_id: BytesN::from_array(e, &tx_id),
+                    op_id: 1,
+                    destination: destination.clone(),
+                    token_in: setup.token_in.address.clone(),
+                    amount_in: 100,
+                    fee: 10,
+                    refund_to: refund_to.clone(),
+                    amount_refunded: 110,
+                }
+                .into_val(e),
+            )
+        ]
+    );
+
+    assert_eq!(setup.swap_pool.get_requests(&destination), Vec::new(e));
+    assert_eq!(
+        setup
+            .swap_pool
+            .get_completed_requests_details(&destination, &0),
+        vec![
+            e,
+            CompletedSwapRequest {
+                tx_id: BytesN::from_array(e, &tx_id),
+                op_id: 1,
+                destination: destination.clone(),
+                token_in: setup.token_in.address.clone(),
+                amount_in: 100,
+                token_out: setup.token_out.address.clone(),
+                amount_out: 0,
+                status: RequestStatus::Refunded,
+                venue_id: None,
+            }
+        ]
+    );
+}
+
+#[test]
+fn test_cancel_request_by_admin() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    setup
+        .swap_pool
+        .set_operational_fee(&setup.operator, &setup.token_in.address, &10);
+
+    setup.add_request(1, &destination, 110);
+    setup.add_request(2, &destination, 110);
+    // admin is able to send funds anywhere
+    let refund_to = Address::generate(e);
+    assert_eq!(
+        setup
+            .swap_pool
+            .cancel_request(&setup.admin, &destination, &2, &refund_to, &false),
+        100
+    );
+    assert_eq!(setup.token_in.balance(&refund_to), 100);
+    assert_eq!(
+        setup
+            .swap_pool
+            .get_accumulated_fees(&setup.token_in.address),
+        20
+    );
+    assert_eq!(setup.swap_pool.get_requests(&destination).len(), 1);
+
+    // cancelled request cannot be fulfilled anymore, while the other one is still there
+    setup.swap_pool.swap_chained_via_router(
+        &setup.operator,
+        &destination,
+        &1,
+        &setup.swaps_chain,
+        &90,
+    );
+    assert_eq!(
+        setup
+            .swap_pool
+            .get_completed_requests_details(&destination, &0)
+            .iter()
+            .map(|r| r.status)
+            .collect::<std::vec::Vec<_>>(),
+        std::vec![RequestStatus::Refunded, RequestStatus::Completed]
+    );
+}
+
+#[should_panic(expected = "Error(Contract, #2302)")]
+#[test]
+fn test_cancel_request_unauthorized() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    let user = Address::generate(e);
+
+    setup.add_request(1, &destination, 100);
+    setup
+        .swap_pool
+        .cancel_request(&user, &destination, &1, &user, &false);
+}
+
+#[test]
+fn test_cancel_request_refund_target() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    let refund_address = Address::generate(e);
+
+    setup.add_request(1, &destination, 100);
+    setup.add_request(2, &destination, 100);
+
+    // operator can't redirect refund away from the proxy wallet
+    let operator_wallet = Address::generate(e);
+    assert_contract_error(
+        setup.swap_pool.try_cancel_request(
+            &setup.operator,
+            &destination,
+            &1,
+            &operator_wallet,
+            &false,
+        ),
+        102,
+    );
+    assert_eq!(setup.token_in.balance(&operator_wallet), 0);
+
+    // nor away from the refund address once it's configured
+    setup
+        .swap_pool
+        .set_refund_address(&Some(refund_address.clone()));
+    assert_contract_error(
+        setup.swap_pool.try_cancel_request(
+            &setup.operator,
+            &destination,
+            &1,
+            &setup.proxy_wallet,
+            &false,
+        ),
+        102,
+    );
+    setup
+        .swap_pool
+        .cancel_request(&setup.operator, &destination, &1, &refund_address, &false);
+    assert_eq!(setup.token_in.balance(&refund_address), 100);
+    assert_eq!(setup.swap_pool.get_requests(&destination).len(), 1);
+}
+
+#[test]
+fn test_reclaim_expired_request() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    let refund_address = Address::generate(e);
+    setup.swap_pool.set_request_ttl(&3600);
+
+    e.ledger().with_mut(|li| li.timestamp = 1000);
+    setup.add_request(1, &destination, 100);
+    setup.add_request(2, &destination, 100);
+    assert_eq!(setup.token_in.balance(&setup.proxy_wallet), 9800);
+
+    // not expired yet
+    e.ledger().with_mut(|li| li.timestamp = 4599);
+    assert!(setup
+        .swap_pool
+        .try_reclaim_expired_request(&destination, &1)
+        .is_err());
+
+    // funds are returned to proxy wallet unless refund address is configured
+    e.ledger().with_mut(|li| li.timestamp = 4600);
+    assert_eq!(
+        setup.swap_pool.reclaim_expired_request(&destination, &1),
+        100
+    );
+    assert_eq!(setup.token_in.balance(&setup.proxy_wallet), 9900);
+
+    setup
+        .swap_pool
+        .set_refund_address(&Some(refund_address.clone()));
+    assert_eq!(
+        setup.swap_pool.reclaim_expired_request(&destination, &2),
+        100
+    );
+    assert_eq!(setup.token_in.balance(&refund_address), 100);
+    assert_eq!(setup.token_in.balance(&setup.swap_pool.address), 0);
+
+    assert_eq!(setup.swap_pool.get_requests(&destination), Vec::new(e));
+    assert_eq!(
+        setup
+            .swap_pool
+            .get_completed_requests_details(&destination, &0)
+            .iter()
+            .map(|r| r.status)
+            .collect::<std::vec::Vec<_>>(),
+        std::vec![RequestStatus::Expired, RequestStatus::Expired]
+    );
+}
+
+#[test]
+fn test_request_ttl_disabled_expiry() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    setup.swap_pool.set_request_ttl(&u64::MAX);
+
+    e.ledger().with_mut(|li| li.timestamp = 1000);
+    setup.add_request(1, &destination, 100);
+    assert_eq!(
+        setup.swap_pool.get_request_by_op_id(&1).0.expires_at,
+        u64::MAX
+    );
+
+    e.ledger().with_mut(|li| li.timestamp = u64::MAX - 1);
+    assert!(setup
+        .swap_pool
+        .try_reclaim_expired_request(&destination, &1)
+        .is_err());
+}
+
+#[should_panic(expected = "Error(Contract, #2305)")]
+#[test]
+fn test_reclaim_not_expired_request() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+
+    setup.add_request(1, &destination, 100);
+    let ttl = setup.swap_pool.get_request_ttl();
+    e.ledger().with_mut(|li| li.timestamp += ttl - 1);
+    setup.swap_pool.reclaim_expired_request(&destination, &1);
+}
+
+#[test]
+fn test_admin_transfer() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let new_admin = Address::generate(&e);
+
+    let swap_pool = deploy_swap_pool(&e);
+    swap_pool.set_admin(&admin);
+    assert_eq!(swap_pool.get_future_admin(), None);
+
+    e.ledger().with_mut(|li| li.timestamp = 1000);
+    swap_pool.propose_admin(&new_admin, &Some(2000));
+    assert_eq!(
+        last_event(&e),
+        vec![
+            &e,
+            (
+                swap_pool.address.clone(),
+                (Symbol::new(&e, "admin_transfer_proposed"), 1_u32).into_val(&e),
+                AdminTransferProposedEvent {
+                    admin: admin.clone(),
+                    future_admin: new_admin.clone(),
+                    deadline: Some(2000),
+                }
+                .into_val(&e),
+            )
+        ]
+    );
+    assert_eq!(swap_pool.get_admin(), Some(admin.clone()));
+    assert_eq!(swap_pool.get_future_admin(), Some(new_admin.clone()));
+
+    e.ledger().with_mut(|li| li.timestamp = 2000);
+    swap_pool.accept_admin();
+    assert_eq!(
+        e.auths(),
+        std::vec![(
+            new_admin.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    swap_pool.address.clone(),
+                    Symbol::new(&e, "accept_admin"),
+                    Vec::new(&e),
+                )),
+                sub_invocations: std::vec![],
+            }
+        )]
+    );
+    assert_eq!(
+        last_event(&e),
+        vec![
+            &e,
+            (
+                swap_pool.address.clone(),
+                (Symbol::new(&e, "admin_transfer_accepted"), 1_u32).into_val(&e),
+                AdminTransferAcceptedEvent {
+                    previous_admin: admin.clone(),
+                    admin: new_admin.clone(),
+                }
+                .into_val(&e),
+            )
+        ]
+    );
+    assert_eq!(swap_pool.get_admin(), Some(new_admin.clone()));
+    assert_eq!(swap_pool.get_future_admin(), None);
+}
+
+#[should_panic(expected = "Error(Contract, #105)")]
+#[test]
+fn test_admin_transfer_expired() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let swap_pool = deploy_swap_pool(&e);
+    swap_pool.set_admin(&Address::generate(&e));
+
+    e.ledger().with_mut(|li| li.timestamp = 1000);
+    swap_pool.propose_admin(&Address::generate(&e), &Some(2000));
+    e.ledger().with_mut(|li| li.timestamp = 2001);
+    swap_pool.accept_admin();
+}
+
+#[should_panic(expected = "Error(Contract, #104)")]
+#[test]
+fn test_admin_transfer_cancelled() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let swap_pool = deploy_swap_pool(&e);
+    swap_pool.set_admin(&Address::generate(&e));
+
+    swap_pool.propose_admin(&Address::generate(&e), &None);
+    swap_pool.cancel_admin_transfer();
+    assert_eq!(swap_pool.get_future_admin(), None);
+    swap_pool.accept_admin();
+}
+
+#[test]
+fn test_multiple_operators() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    let operator2 = Address::generate(e);
+
+    setup.swap_pool.grant_role(&Role::Operator, &operator2);
+    assert_eq!(
+        setup.swap_pool.get_role_members(&Role::Operator),
+        vec![e, setup.operator.clone(), operator2.clone()]
+    );
+    assert_eq!(
+        setup.swap_pool.get_role_members(&Role::Admin),
+        vec![e, setup.admin.clone()]
+    );
+    assert!(setup.swap_pool.has_role(&Role::Operator, &operator2));
+    assert!(!setup.swap_pool.has_role(&Role::FeeManager, &operator2));
+
+    // request added by one operator may be fulfilled by another one
+    setup.add_request(1, &destination, 100);
+    setup
+        .swap_pool
+        .swap_chained_via_router(&operator2, &destination, &1, &setup.swaps_chain, &90);
+    assert_eq!(setup.token_out.balance(&destination), 98);
+
+    setup.swap_pool.revoke_role(&Role::Operator, &operator2);
+    assert!(!setup.swap_pool.has_role(&Role::Operator, &operator2));
+    setup.add_request(2, &destination, 100);
+    assert!(setup
+        .swap_pool
+        .try_swap_chained_via_router(&operator2, &destination, &2, &setup.swaps_chain, &90)
+        .is_err());
+
+    // set_operator replaces all the operators
+    setup.swap_pool.grant_role(&Role::Operator, &operator2);
+    setup.swap_pool.set_operator(&operator2);
+    assert_eq!(
+        setup.swap_pool.get_role_members(&Role::Operator),
+        vec![e, operator2.clone()]
+    );
+}
+
+#[should_panic(expected = "Error(Contract, #107)")]
+#[test]
+fn test_grant_admin_role() {
+    let setup = Setup::default();
+    setup
+        .swap_pool
+        .grant_role(&Role::Admin, &Address::generate(&setup.e));
+}
+
+#[test]
+fn test_pause() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    let pauser = Address::generate(e);
+    setup.swap_pool.grant_role(&Role::Pauser, &pauser);
+
+    // intake
+    setup.swap_pool.pause(&pauser, &PauseTarget::Intake);
+    assert!(setup.swap_pool.is_paused(&PauseTarget::Intake));
+    assert_contract_error(
+        setup.swap_pool.try_add_request(
+            &setup.operator,
+            &setup.proxy_wallet,
+            &BytesN::from_array(e, &[0; 32]),
+            &1,
+            &destination,
+            &setup.token_in.address,
+            &100,
+        ),
+        PoolError::Paused as u32,
+    );
+    // pauser is not able to unpause
+    assert!(setup
+        .swap_pool
+        .try_unpause(&pauser, &PauseTarget::Intake)
+        .is_err());
+    setup.swap_pool.unpause(&setup.admin, &PauseTarget::Intake);
+    assert!(!setup.swap_pool.is_paused(&PauseTarget::Intake));
+    setup.add_request(1, &destination, 100);
+
+    // swaps
+    setup.swap_pool.pause(&pauser, &PauseTarget::Swaps);
+    assert_contract_error(
+        setup.swap_pool.try_swap_chained_via_router(
+            &setup.operator,
+            &destination,
+            &1,
+            &setup.swaps_chain,
+            &90,
+        ),
+        PoolError::Paused as u32,
+    );
+    setup.add_request(2, &destination, 100);
+    setup.swap_pool.unpause(&setup.admin, &PauseTarget::Swaps);
+
+    // single token
+    let token_out = PauseTarget::Token(setup.token_out.address.clone());
+    setup.swap_pool.pause(&setup.admin, &token_out);
+    assert_contract_error(
+        setup.swap_pool.try_swap_chained_via_router(
+            &setup.operator,
+            &destination,
+            &1,
+            &setup.swaps_chain,
+            &90,
+        ),
+        PoolError::Paused as u32,
+    );
+    // funds are still able to leave the contract
+    setup.swap_pool.cancel_request(
+        &setup.operator,
+        &destination,
+        &2,
+        &setup.proxy_wallet,
+        &false,
+    );
+    setup.swap_pool.unpause(&setup.admin, &token_out);
+    setup.swap_pool.swap_chained_via_router(
+        &setup.operator,
+        &destination,
+        &1,
+        &setup.swaps_chain,
+        &90,
+    );
+    assert_eq!(setup.token_out.balance(&destination), 98);
+}
+
+#[should_panic(expected = "Error(Contract, #106)")]
+#[test]
+fn test_pause_unauthorized() {
+    let setup = Setup::default();
+    let user = Address::generate(&setup.e);
+    setup.swap_pool.pause(&user, &PauseTarget::Swaps);
+}
+
+#[test]
+fn test_entry_points_auth() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let pool = &setup.swap_pool.address;
+    let admin = &setup.admin;
+    let operator = &setup.operator;
+    let destination = Address::generate(e);
+    let pauser = Address::generate(e);
+    let fee_manager = Address::generate(e);
+    let token = setup.token_in.address.clone();
+
+    // roles are managed by admin
+    setup.swap_pool.grant_role(&Role::Pauser, &pauser);
+    assert_auth(
+        e,
+        admin,
+        pool,
+        "grant_role",
+        vec![e, Role::Pauser.into_val(e), pauser.to_val()],
+    );
+    setup.swap_pool.grant_role(&Role::FeeManager, &fee_manager);
+    setup.swap_pool.revoke_role(&Role::FeeManager, &fee_manager);
+    assert_auth(
+        e,
+        admin,
+        pool,
+        "revoke_role",
+        vec![e, Role::FeeManager.into_val(e), fee_manager.to_val()],
+    );
+    setup.swap_pool.grant_role(&Role::FeeManager, &fee_manager);
+
+    // pause is authorized by the pauser, unpause by the admin
+    setup.swap_pool.pause(&pauser, &PauseTarget::Swaps);
+    assert_auth(
+        e,
+        &pauser,
+        pool,
+        "pause",
+        vec![e, pauser.to_val(), PauseTarget::Swaps.into_val(e)],
+    );
+    setup.swap_pool.unpause(admin, &PauseTarget::Swaps);
+    assert_auth(
+        e,
+        admin,
+        pool,
+        "unpause",
+        vec![e, admin.to_val(), PauseTarget::Swaps.into_val(e)],
+    );
+
+    // cancellation is authorized by the operator
+    setup.add_request(1, &destination, 100);
+    setup
+        .swap_pool
+        .cancel_request(operator, &destination, &1, &setup.proxy_wallet, &false);
+    assert_auth(
+        e,
+        operator,
+        pool,
+        "cancel_request",
+        vec![
+            e,
+            operator.to_val(),
+            destination.to_val(),
+            1_u128.into_val(e),
+            setup.proxy_wallet.to_val(),
+            false.into_val(e),
+        ],
+    );
+
+    // fees are withdrawn by the fee manager
+    setup.swap_pool.set_operational_fee(operator, &token, &10);
+    setup.add_request(2, &destination, 100);
+    setup.swap_pool.set_fee_treasury(&Address::generate(e));
+    setup.swap_pool.withdraw_fees(&fee_manager, &token);
+    assert_auth(
+        e,
+        &fee_manager,
+        pool,
+        "withdraw_fees",
+        vec![e, fee_manager.to_val(), token.to_val()],
+    );
+
+    setup.swap_pool.set_max_operational_fee(&token, &20);
+    assert_auth(
+        e,
+        admin,
+        pool,
+        "set_max_operational_fee",
+        vec![e, token.to_val(), 20_i128.into_val(e)],
+    );
+
+    let venue = Venue::Pool(setup.liquidity_pool.clone());
+    setup.swap_pool.register_venue(&1, &venue);
+    assert_auth(
+        e,
+        admin,
+        pool,
+        "register_venue",
+        vec![e, 1_u32.into_val(e), venue.into_val(e)],
+    );
+
+    // preferences are set by the destination itself
+    setup.swap_pool.set_destination_preferences(
+        &destination,
+        &setup.token_out.address,
+        &100,
+        &None,
+    );
+    assert_auth(
+        e,
+        &destination,
+        pool,
+        "set_destination_preferences",
+        vec![
+            e,
+            destination.to_val(),
+            setup.token_out.address.to_val(),
+            100_u32.into_val(e),
+            None::<Address>.into_val(e),
+        ],
+    );
+
+    // admin transfer is proposed by the admin and accepted by the new one
+    let new_admin = Address::generate(e);
+    setup.swap_pool.propose_admin(&new_admin, &None);
+    assert_auth(
+        e,
+        admin,
+        pool,
+        "propose_admin",
+        vec![e, new_admin.to_val(), None::<u64>.into_val(e)],
+    );
+    setup.swap_pool.accept_admin();
+    assert_auth(e, &new_admin, pool, "accept_admin", vec![e]);
+}
+
+#[test]
+fn test_operational_fee_schedule() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    let token = setup.token_in.address.clone();
+
+    let schedule = FeeSchedule {
+        flat: 5,
+        rate_bps: 100,
+        min: 7,
+        max: Some(20),
+    };
+    setup
+        .swap_pool
+        .set_operational_fee_schedule(&setup.operator, &token, &schedule);
+    assert_eq!(
+        setup.swap_pool.get_operational_fee_schedule(&token),
+        schedule
+    );
+    assert_eq!(setup.swap_pool.get_operational_fee(&token), 5);
+
+    // floor, flat + percentage, cap
+    assert_eq!(setup.swap_pool.quote_operational_fee(&token, &100), 7);
+    assert_eq!(setup.swap_pool.quote_operational_fee(&token, &1099), 15);
+    assert_eq!(setup.swap_pool.quote_operational_fee(&token, &10000), 20);
+
+    setup.add_request(1, &destination, 1099);
+    assert_eq!(setup.swap_pool.get_accumulated_fees(&token), 15);
+    assert_eq!(
+        setup.swap_pool.get_requests(&destination).get(0).unwrap().4,
+        1084
+    );
+
+    // flat fee setter is still supported
+    setup
+        .swap_pool
+        .set_operational_fee(&setup.operator, &token, &3);
+    assert_eq!(
+        setup.swap_pool.get_operational_fee_schedule(&token),
+        FeeSchedule {
+            flat: 3,
+            rate_bps: 0,
+            min: 0,
+            max: None,
+        }
+    );
+    assert_eq!(setup.swap_pool.quote_operational_fee(&token, &10000), 3);
+}
+
+#[test]
+fn test_max_operational_fee() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    let token = setup.token_in.address.clone();
+
+    setup.swap_pool.set_max_operational_fee(&token, &10);
+    assert_eq!(setup.swap_pool.get_max_operational_fee(&token), Some(10));
+
+    assert_contract_error(
+        setup
+            .swap_pool
+            .try_set_operational_fee(&setup.operator, &token, &-1),
+        PoolError::NegativeFee as u32,
+    );
+    assert_contract_error(
+        setup
+            .swap_pool
+            .try_set_operational_fee(&setup.operator, &token, &11),
+        PoolError::FeeAboveMaximum as u32,
+    );
+    assert_contract_error(
+        setup.swap_pool.try_set_operational_fee_schedule(
+            &setup.operator,
+            &token,
+            &FeeSchedule {
+                flat: 0,
+                rate_bps: 0,
+                min: 11,
+                max: None,
+            },
+        ),
+        PoolError::FeeAboveMaximum as u32,
+    );
+
+    // percentage part has to be capped within the ceiling
+    for max in [None, Some(11)] {
+        assert_contract_error(
+            setup.swap_pool.try_set_operational_fee_schedule(
+                &setup.operator,
+                &token,
+                &FeeSchedule {
+                    flat: 0,
+                    rate_bps: 1000,
+                    min: 0,
+                    max,
+                },
+            ),
+            PoolError::FeeAboveMaximum as u32,
+        );
+    }
+    assert_contract_error(
+        setup.swap_pool.try_set_operational_fee_schedule(
+            &setup.operator,
+            &token,
+            &FeeSchedule {
+                flat: 0,
+                rate_bps: 10001,
+                min: 0,
+                max: Some(10),
+            },
+        ),
+        PoolError::InvalidFeeRate as u32,
+    );
+    assert_contract_error(
+        setup.swap_pool.try_set_operational_fee_schedule(
+            &setup.operator,
+            &token,
+            &FeeSchedule {
+                flat: 0,
+                rate_bps: 1000,
+                min: 8,
+                max: Some(7),
+            },
+        ),
+        PoolError::InvalidFeeBounds as u32,
+    );
+
+    setup.swap_pool.set_operational_fee_schedule(
+        &setup.operator,
+        &token,
+        &FeeSchedule {
+            flat: 0,
+            rate_bps: 1000,
+            min: 0,
+            max: Some(10),
+        },
+    );
+    assert_eq!(setup.swap_pool.quote_operational_fee(&token, &50), 5);
+    assert_eq!(setup.swap_pool.quote_operational_fee(&token, &1000), 10);
+
+    // lowered ceiling still applies to schedule set before
+    setup.swap_pool.set_max_operational_fee(&token, &8);
+    assert_eq!(setup.swap_pool.quote_operational_fee(&token, &1000), 8);
+    setup.add_request(1, &destination, 1000);
+    assert_eq!(setup.swap_pool.get_accumulated_fees(&token), 8);
+}
+
+#[should_panic(expected = "Error(Contract, #2309)")]
+#[test]
+fn test_operational_fee_exceeds_amount() {
+    let setup = Setup::default();
+    setup
+        .swap_pool
+        .set_operational_fee(&setup.operator, &setup.token_in.address, &10);
+    setup.add_request(1, &Address::generate(&setup.e), 9);
+}
+
+#[test]
+fn test_operational_fee_equals_amount() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    setup
+        .swap_pool
+        .set_operational_fee(&setup.operator, &setup.token_in.address, &10);
+
+    let destination = Address::generate(e);
+    let request = |op_id: u128| RequestDescriptor {
+        proxy_wallet: setup.proxy_wallet.clone(),
+        tx_id: BytesN::from_array(e, &[op_id as u8; 32]),
+        op_id,
+        destination: destination.clone(),
+        token_in: setup.token_in.address.clone(),
+        amount_in: 10,
+    };
+    assert_contract_error(
+        setup.swap_pool.try_add_request(
+            &setup.operator,
+            &setup.proxy_wallet,
+            &BytesN::from_array(e, &[1; 32]),
+            &1,
+            &destination,
+            &setup.token_in.address,
+            &10,
+        ),
+        PoolError::FeeExceedsAmount as u32,
+    );
+    // batch of requests consisting of the fee only is rejected as well
+    assert_contract_error(
+        setup
+            .swap_pool
+            .try_add_requests_batch(&setup.operator, &vec![e, request(1), request(2)]),
+        PoolError::FeeExceedsAmount as u32,
+    );
+    assert_eq!(setup.token_in.balance(&setup.proxy_wallet), 10000);
+}
+
+#[test]
+fn test_withdraw_fees() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    let fee_manager = Address::generate(e);
+    let treasury = Address::generate(e);
+    let token = setup.token_in.address.clone();
+    setup.swap_pool.grant_role(&Role::FeeManager, &fee_manager);
+    setup
+        .swap_pool
+        .set_operational_fee(&setup.operator, &token, &10);
+
+    setup.add_request(1, &destination, 110);
+    setup.add_request(2, &destination, 110);
+    assert_eq!(setup.token_in.balance(&setup.operator), 0);
+    assert_eq!(setup.token_in.balance(&setup.swap_pool.address), 220);
+    assert_eq!(setup.swap_pool.get_accumulated_fees(&token), 20);
+
+    assert_contract_error(
+        setup.swap_pool.try_withdraw_fees(&fee_manager, &token),
+        PoolError::FeeTreasuryNotSet as u32,
+    );
+    setup.swap_pool.set_fee_treasury(&treasury);
+    assert_eq!(setup.swap_pool.get_fee_treasury(), Some(treasury.clone()));
+
+    // operator is not able to withdraw fees
+    assert!(setup
+        .swap_pool
+        .try_withdraw_fees(&setup.operator, &token)
+        .is_err());
+    assert_eq!(setup.swap_pool.withdraw_fees(&fee_manager, &token), 20);
+    assert_eq!(setup.token_in.balance(&treasury), 20);
+    assert_eq!(setup.token_in.balance(&setup.swap_pool.address), 200);
+    assert_eq!(setup.swap_pool.get_accumulated_fees(&token), 0);
+    assert_eq!(setup.swap_pool.get_collected_fees(&token), 20);
+
+    // fee already withdrawn can't be refunded from the contract
+    assert_contract_error(
+        setup.swap_pool.try_cancel_request(
+            &setup.operator,
+            &destination,
+            &1,
+            &setup.proxy_wallet,
+            &true,
+        ),
+        PoolError::NotEnoughAccumulatedFees as u32,
+    );
+    setup.swap_pool.cancel_request(
+        &setup.operator,
+        &destination,
+        &1,
+        &setup.proxy_wallet,
+        &false,
+    );
+    assert_eq!(setup.token_in.balance(&setup.proxy_wallet), 9880);
+}
+
+#[test]
+fn test_swap_chain_validation() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    setup.add_request(1, &destination, 100);
+
+    let (pair, pool_index, _) = setup.swaps_chain.get(0).unwrap();
+    let unsorted = Vec::from_array(e, [pair.get(1).unwrap(), pair.get(0).unwrap()]);
+    let token_in = setup.token_in.address.clone();
+    let token_out = setup.token_out.address.clone();
+    let mut other_pair = std::vec![token_out.clone(), Address::generate(e)];
+    other_pair.sort();
+    let other_token = if other_pair[0] == token_out {
+        other_pair[1].clone()
+    } else {
+        other_pair[0].clone()
+    };
+
+    for (swaps_chain, error) in [
+        (Vec::new(e), PoolError::InvalidSwapChain),
+        (
+            vec![e, (unsorted, pool_index.clone(), token_out.clone())],
+            PoolError::SwapChainTokensNotSorted,
+        ),
+        (
+            vec![
+                e,
+                (
+                    Vec::from_array(e, [other_pair[0].clone(), other_pair[1].clone()]),
+                    pool_index.clone(),
+                    other_token.clone(),
+                ),
+            ],
+            PoolError::SwapChainTokenInMismatch,
+        ),
+        (
+            vec![e, (pair.clone(), pool_index.clone(), token_in.clone())],
+            PoolError::SwapChainBroken,
+        ),
+        (
+            vec![
+                e,
+                (pair.clone(), pool_index.clone(), token_out.clone()),
+                (pair.clone(), pool_index.clone(), token_in.clone()),
+            ],
+            PoolError::SwapChainTokenOutMismatch,
+        ),
+    ] {
+        assert_contract_error(
+            setup.swap_pool.try_swap_chained_via_router(
+                &setup.operator,
+                &destination,
+                &1,
+                &swaps_chain,
+                &90,
+            ),
+            error as u32,
+        );
+    }
+}
+
+#[test]
+fn test_router_output_verification() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    let token_out = setup.token_out.address.clone();
+    setup.add_request(1, &destination, 100);
+
+    // router reports more than it has actually sent
+    let router = deploy_mock_router(e, &token_out, 90, 100);
+    setup.swap_pool.set_swap_router(&router.address);
+    assert_contract_error(
+        setup.swap_pool.try_swap_chained_via_router(
+            &setup.operator,
+            &destination,
+            &1,
+            &setup.swaps_chain,
+            &90,
+        ),
+        PoolError::SwapOutputMismatch as u32,
+    );
+
+    // router ignores out_min
+    let router = deploy_mock_router(e, &token_out, 80, 80);
+    setup.swap_pool.set_swap_router(&router.address);
+    assert_contract_error(
+        setup.swap_pool.try_swap_chained_via_router(
+            &setup.operator,
+            &destination,
+            &1,
+            &setup.swaps_chain,
+            &90,
+        ),
+        PoolError::SwapOutputBelowMinimum as u32,
+    );
+
+    // only received amount is forwarded to destination
+    let router = deploy_mock_router(e, &token_out, 95, 95);
+    setup.swap_pool.set_swap_router(&router.address);
+    assert_eq!(
+        setup.swap_pool.swap_chained_via_router(
+            &setup.operator,
+            &destination,
+            &1,
+            &setup.swaps_chain,
+            &90,
+        ),
+        95
+    );
+    assert_eq!(setup.token_out.balance(&destination), 95);
+    assert_eq!(setup.token_out.balance(&setup.swap_pool.address), 0);
+}
+
+#[test]
+fn test_request_lookup_budget() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let pool = &setup.swap_pool.address;
+
+    // every destination is filled before measuring, so all the measurements see the same
+    // amount of data in storage and only the number of destination's pending requests differs
+    let add_legacy_requests = |count: u128| {
+        let destination = Address::generate(e);
+        let mut requests = Vec::new(e);
+        for op_id in 0..count {
+            requests.push_back(LegacySwapRequest {
+                tx_id: BytesN::from_array(e, &[0; 32]),
+                op_id,
+                destination: destination.clone(),
+                token_in: setup.token_in.address.clone(),
+                amount_in: 10,
+                token_out: setup.token_out.address.clone(),
+            });
+        }
+        let key = legacy::DataKey::SwapRequests(destination);
+        e.as_contract(pool, || e.storage().persistent().set(&key, &requests));
+        key
+    };
+    let add_requests = |first_op_id: u128, count: u128| {
+        let destination = Address::generate(e);
+        for op_id in first_op_id..first_op_id + count {
+            setup.add_request(op_id, &destination, 10);
+        }
+        destination
+    };
+    let legacy_single = add_legacy_requests(1);
+    let legacy_many = add_legacy_requests(100);
+    let single = add_requests(1, 1);
+    let many = add_requests(2, 100);
+
+    // request taken out of pending requests stored the way version 104 did:
+    // whole vector is read, scanned for the operation id and written back
+    let legacy_cost = |key: &legacy::DataKey, op_id: u128| {
+        e.budget().reset_default();
+        e.as_contract(pool, || {
+            let mut requests: Vec<LegacySwapRequest> = e.storage().persistent().get(key).unwrap();
+            let index = requests.iter().position(|r| r.op_id == op_id).unwrap();
+            requests.remove(index as u32);
+            e.storage().persistent().set(key, &requests);
+        });
+        let cost = e.budget().cpu_instruction_cost();
+        e.budget().reset_unlimited();
+        cost
+    };
+    // same in the current layout, request is read by its operation id and unlinked
+    let cost = |destination: &Address, op_id: u128| {
+        e.budget().reset_default();
+        e.as_contract(pool, || {
+            let request = storage::get_swap_request_by_id(e, destination, op_id);
+            storage::set_swap_request_refunded(e, destination, request);
+        });
+        let cost = e.budget().cpu_instruction_cost();
+        e.budget().reset_unlimited();
+        cost
+    };
+
+    let legacy_single_cost = legacy_cost(&legacy_single, 0);
+    let legacy_many_cost = legacy_cost(&legacy_many, 50);
+    let single_cost = cost(&single, 1);
+    let many_cost = cost(&many, 52);
+
+    // legacy cost grows with the number of pending requests, current one doesn't. absolute
+    // costs are dominated by the size of test ledger, so growth of both layouts is compared
+    assert!(legacy_many_cost > legacy_single_cost * 5);
+    assert!(many_cost < single_cost * 2);
+    assert!((many_cost - single_cost) * 4 < legacy_many_cost - legacy_single_cost);
+}
+
+#[test]
+fn test_get_request_by_id() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    let refund_to = setup.proxy_wallet.clone();
+
+    setup.add_request(1, &destination, 100);
+    setup.add_request(2, &destination, 50);
+
+    let mut tx_id = [0; 32];
+    tx_id[15] = 1;
+    let tx_id = BytesN::from_array(e, &tx_id);
+    let (request, status, amount_out) = setup.swap_pool.get_request_by_op_id(&1);
+    assert_eq!(request.tx_id, tx_id);
+    assert_eq!(request.destination, destination);
+    assert_eq!(request.amount_in, 100);
+    assert_eq!(status, RequestStatus::Pending);
+    assert_eq!(amount_out, 0);
+    assert_eq!(
+        setup.swap_pool.get_request_by_tx_id(&tx_id),
+        (request, RequestStatus::Pending, 0)
+    );
+
+    // processed requests remain available
+    setup.swap_pool.swap_chained_via_router(
+        &setup.operator,
+        &destination,
+        &1,
+        &setup.swaps_chain,
+        &0,
+    );
+    let (request, status, amount_out) = setup.swap_pool.get_request_by_tx_id(&tx_id);
+    assert_eq!(request.op_id, 1);
+    assert_eq!(status, RequestStatus::Completed);
+    assert_eq!(amount_out, 98);
+
+    setup
+        .swap_pool
+        .cancel_request(&setup.operator, &destination, &2, &refund_to, &false);
+    let (request, status, amount_out) = setup.swap_pool.get_request_by_op_id(&2);
+    assert_eq!(request.amount_in, 50);
+    assert_eq!(status, RequestStatus::Refunded);
+    assert_eq!(amount_out, 0);
+
+    assert!(setup.swap_pool.try_get_request_by_op_id(&3).is_err());
+    assert!(setup
+        .swap_pool
+        .try_get_request_by_tx_id(&BytesN::from_array(e, &[1; 32]))
+        .is_err());
+}
+
+#[test]
+fn test_duplicate_tx_id() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+
+    let tx_id = BytesN::from_array(e, &[7; 32]);
+    setup.swap_pool.add_request(
+        &setup.operator,
+        &setup.proxy_wallet,
+        &tx_id,
+        &1,
+        &destination,
+        &setup.token_in.address,
+        &100,
+    );
+    assert_contract_error(
+        setup.swap_pool.try_add_request(
+            &setup.operator,
+            &setup.proxy_wallet,
+            &tx_id,
+            &2,
+            &destination,
+            &setup.token_in.address,
+            &100,
+        ),
+        PoolError::TxIdAlreadyConsumed as u32,
+    );
+
+    // tx id stays consumed after the request is processed
+    setup.swap_pool.swap_chained_via_router(
+        &setup.operator,
+        &destination,
+        &1,
+        &setup.swaps_chain,
+        &0,
+    );
+    assert_contract_error(
+        setup.swap_pool.try_add_request(
+            &setup.operator,
+            &setup.proxy_wallet,
+            &tx_id,
+            &2,
+            &destination,
+            &setup.token_in.address,
+            &100,
+        ),
+        PoolError::TxIdAlreadyConsumed as u32,
+    );
+    assert_eq!(setup.token_in.balance(&setup.proxy_wallet), 9900);
+}
+
+#[test]
+fn test_out_of_order_operation_ids() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+
+    let try_add_request = |op_id: u128| {
+        let mut tx_id = [0; 32];
+        tx_id[..16].copy_from_slice(&op_id.to_be_bytes());
+        tx_id[31] = 1;
+        setup.swap_pool.try_add_request(
+            &setup.operator,
+            &setup.proxy_wallet,
+            &BytesN::from_array(e, &tx_id),
+            &op_id,
+            &destination,
+            &setup.token_in.address,
+            &10,
+        )
+    };
+
+    // without window operation ids are strictly increasing
+    assert_eq!(setup.swap_pool.get_operation_id_window(), 0);
+    setup.add_request(2, &destination, 10);
+    assert_contract_error(
+        try_add_request(1),
+        PoolError::OperationIdAlreadyConsumed as u32,
+    );
+
+    setup.swap_pool.set_operation_id_window(&3);
+    assert_eq!(setup.swap_pool.get_operation_id_window(), 3);
+    setup.add_request(6, &destination, 10);
+    setup.add_request(4, &destination, 10);
+    setup.add_request(5, &destination, 10);
+    assert_eq!(setup.swap_pool.get_last_operation_id(), 6);
+
+    // replays within the window are still rejected
+    assert_contract_error(
+        try_add_request(4),
+        PoolError::OperationIdAlreadyConsumed as u32,
+    );
+    assert_contract_error(
+        try_add_request(2),
+        PoolError::OperationIdAlreadyConsumed as u32,
+    );
+    // ids below the window are rejected even if never seen
+    assert_contract_error(
+        try_add_request(3),
+        PoolError::OperationIdAlreadyConsumed as u32,
+    );
+
+    // processed request stays consumed
+    setup.swap_pool.swap_chained_via_router(
+        &setup.operator,
+        &destination,
+        &5,
+        &setup.swaps_chain,
+        &0,
+    );
+    assert_contract_error(
+        try_add_request(5),
+        PoolError::OperationIdAlreadyConsumed as u32,
+    );
+}
+
+#[test]
+fn test_add_requests_batch() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    let other_destination = Address::generate(e);
+    setup
+        .swap_pool
+        .set_operational_fee(&setup.operator, &setup.token_in.address, &1);
+
+    let descriptor = |op_id: u128, destination: &Address, amount_in: i128| {
+        let mut tx_id = [0; 32];
+        tx_id[..16].copy_from_slice(&op_id.to_be_bytes());
+        RequestDescriptor {
+            proxy_wallet: setup.proxy_wallet.clone(),
+            tx_id: BytesN::from_array(e, &tx_id),
+            op_id,
+            destination: destination.clone(),
+            token_in: setup.token_in.address.clone(),
+            amount_in,
+        }
+    };
+
+    setup.swap_pool.add_requests_batch(
+        &setup.operator,
+        &vec![
+            e,
+            descriptor(1, &destination, 100),
+            descriptor(2, &other_destination, 50),
+            descriptor(3, &destination, 10),
+        ],
+    );
+    assert_eq!(setup.token_in.balance(&setup.proxy_wallet), 9840);
+    assert_eq!(setup.token_in.balance(&setup.swap_pool.address), 160);
+    assert_eq!(
+        setup.swap_pool.get_collected_fees(&setup.token_in.address),
+        3
+    );
+    assert_eq!(setup.swap_pool.get_requests(&destination).len(), 2);
+    assert_eq!(setup.swap_pool.get_requests(&other_destination).len(), 1);
+    assert_eq!(setup.swap_pool.get_last_operation_id(), 3);
+
+    // any failed item reverts the whole batch
+    assert_contract_error(
+        setup.swap_pool.try_add_requests_batch(
+            &setup.operator,
+            &vec![
+                e,
+                descriptor(4, &destination, 100),
+                descriptor(4, &destination, 100),
+            ],
+        ),
+        PoolError::OperationIdAlreadyConsumed as u32,
+    );
+    assert_eq!(setup.token_in.balance(&setup.proxy_wallet), 9840);
+    assert_eq!(setup.swap_pool.get_last_operation_id(), 3);
+
+    assert_eq!(setup.swap_pool.get_max_batch_size(), MAX_BATCH_SIZE_LIMIT);
+    setup.swap_pool.set_max_batch_size(&2);
+    assert_contract_error(
+        setup.swap_pool.try_add_requests_batch(
+            &setup.operator,
+            &vec![
+                e,
+                descriptor(4, &destination, 10),
+                descriptor(5, &destination, 10),
+                descriptor(6, &destination, 10),
+            ],
+        ),
+        PoolError::BatchTooLarge as u32,
+    );
+    assert_contract_error(
+        setup
+            .swap_pool
+            .try_add_requests_batch(&setup.operator, &Vec::new(e)),
+        PoolError::EmptyBatch as u32,
+    );
+
+    for max_batch_size in [0, MAX_BATCH_SIZE_LIMIT + 1] {
+        assert_contract_error(
+            setup.swap_pool.try_set_max_batch_size(&max_batch_size),
+            PoolError::InvalidBatchSize as u32,
+        );
+    }
+    setup.swap_pool.set_max_batch_size(&MAX_BATCH_SIZE_LIMIT);
+}
+
+#[test]
+fn test_add_requests_batch_resources() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    setup
+        .swap_pool
+        .set_operational_fee(&setup.operator, &setup.token_in.address, &1);
+
+    // each destination already has a pending request, so that its last request is relinked
+    let mut batch = Vec::new(e);
+    for op_id in 1..=MAX_BATCH_SIZE_LIMIT as u128 {
+        let destination = Address::generate(e);
+        setup.add_request(op_id, &destination, 10);
+        batch.push_back(RequestDescriptor {
+            proxy_wallet: setup.proxy_wallet.clone(),
+            tx_id: BytesN::from_array(e, &[op_id as u8; 32]),
+            op_id: op_id + 100,
+            destination,
+            token_in: setup.token_in.address.clone(),
+            amount_in: 10,
+        });
+    }
+
+    // ttl extensions don't count as writes, so only entries data is compared
+    let ledger_entries = || {
+        e.to_ledger_snapshot()
+            .ledger_entries
+            .into_iter()
+            .map(|(key, (entry, _))| (*key, *entry))
+            .collect::<std::vec::Vec<_>>()
+    };
+    let entries_before = ledger_entries();
+
+    e.budget().reset_default();
+    setup.swap_pool.add_requests_batch(&setup.operator, &batch);
+    e.budget().reset_unlimited();
+
+    let entries_after = ledger_entries();
+    let written = entries_after
+        .iter()
+        .filter(|entry| !entries_before.contains(entry))
+        .count()
+        + entries_before
+            .iter()
+            .filter(|(key, _)| !entries_after.iter().any(|(k, _)| k == key))
+            .count();
+    assert!(written as u32 <= TX_MAX_WRITE_LEDGER_ENTRIES);
+    assert_eq!(
+        setup.swap_pool.get_last_operation_id(),
+        MAX_BATCH_SIZE_LIMIT as u128 + 100
+    );
+}
+
+#[test]
+fn test_swap_batch_via_router() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination1 = Address::generate(e);
+    let destination2 = Address::generate(e);
+    let destination3 = Address::generate(e);
+    let router = deploy_mock_router(e, &setup.token_out.address, 100, 100);
+    setup.swap_pool.set_swap_router(&router.address);
+
+    setup.add_request(1, &destination1, 100);
+    setup.add_request(2, &destination2, 50);
+    setup.add_request(3, &destination3, 30);
+    setup.add_request(4, &destination3, 10);
+
+    assert_contract_error(
+        setup.swap_pool.try_swap_batch_via_router(
+            &setup.operator,
+            &vec![e, (destination1.clone(), 1), (destination1.clone(), 1)],
+            &setup.swaps_chain,
+            &0,
+        ),
+        PoolError::DuplicateBatchRequest as u32,
+    );
+    assert_contract_error(
+        setup.swap_pool.try_swap_batch_via_router(
+            &setup.operator,
+            &vec![e],
+            &setup.swaps_chain,
+            &0,
+        ),
+        PoolError::EmptyBatch as u32,
+    );
+    assert_contract_error(
+        setup.swap_pool.try_swap_batch_via_router(
+            &setup.operator,
+            &vec![e, (destination1.clone(), 1), (destination2.clone(), 2)],
+            &setup.swaps_chain,
+            &101,
+        ),
+        PoolError::SwapOutputBelowMinimum as u32,
+    );
+
+    // single router call for the whole input, output is split pro-rata
+    assert_eq!(
+        setup.swap_pool.swap_batch_via_router(
+            &setup.operator,
+            &vec![
+                e,
+                (destination1.clone(), 1),
+                (destination2.clone(), 2),
+                (destination3.clone(), 3),
+            ],
+            &setup.swaps_chain,
+            &100,
+        ),
+        100
+    );
+    assert_eq!(setup.token_in.balance(&router.address), 180);
+    // 100 * 100 / 180 and 100 * 50 / 180 rounded down, remainder goes to the last request
+    assert_eq!(setup.token_out.balance(&destination1), 55);
+    assert_eq!(setup.token_out.balance(&destination2), 27);
+    assert_eq!(setup.token_out.balance(&destination3), 18);
+    assert_eq!(setup.token_out.balance(&setup.swap_pool.address), 0);
+
+    let (_request, status, amount_out) = setup.swap_pool.get_request_by_op_id(&2);
+    assert_eq!(status, RequestStatus::Completed);
+    assert_eq!(amount_out, 27);
+    assert_eq!(setup.swap_pool.get_requests(&destination3).len(), 1);
+    assert_eq!(
+        setup
+            .swap_pool
+            .get_completed_requests_details(&destination3, &0)
+            .get(0)
+            .unwrap()
+            .amount_out,
+        18
+    );
+}
+
+#[test]
+fn test_fulfill_direct() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    let direct_wallet = Address::generate(e);
+    setup
+        .swap_pool
+        .add_proxy_wallet(&direct_wallet, &setup.token_in.address);
+    SorobanTokenAdminClient::new(e, &setup.token_in.address).mint(&direct_wallet, &1000);
+    setup
+        .token_in
+        .approve(&direct_wallet, &setup.swap_pool.address, &i128::MAX, &9999);
+    setup
+        .swap_pool
+        .set_operational_fee(&setup.operator, &setup.token_in.address, &5);
+
+    // request that requires a swap can't be delivered directly
+    setup.add_request(1, &destination, 100);
+    assert_contract_error(
+        setup
+            .swap_pool
+            .try_fulfill_direct(&setup.operator, &destination, &1),
+        PoolError::DirectDeliveryNotAllowed as u32,
+    );
+
+    setup.swap_pool.add_request(
+        &setup.operator,
+        &direct_wallet,
+        &BytesN::from_array(e, &[2; 32]),
+        &2,
+        &destination,
+        &setup.token_in.address,
+        &100,
+    );
+    assert_eq!(
+        setup
+            .swap_pool
+            .fulfill_direct(&setup.operator, &destination, &2),
+        95
+    );
+    assert_eq!(setup.token_in.balance(&destination), 95);
+
+    let (_request, status, amount_out) = setup.swap_pool.get_request_by_op_id(&2);
+    assert_eq!(status, RequestStatus::Completed);
+    assert_eq!(amount_out, 95);
+    let completed = setup
+        .swap_pool
+        .get_completed_requests_details(&destination, &0)
+        .get(0)
+        .unwrap();
+    assert_eq!(completed.amount_in, 95);
+    assert_eq!(completed.amount_out, 95);
+    assert_eq!(completed.token_out, setup.token_in.address);
+}
+
+#[test]
+fn test_proxy_wallets_registry() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    let proxy_wallet2 = Address::generate(e);
+    setup
+        .swap_pool
+        .add_proxy_wallet(&proxy_wallet2, &setup.token_out.address);
+    SorobanTokenAdminClient::new(e, &setup.token_in.address).mint(&proxy_wallet2, &1000);
+    setup
+        .token_in
+        .approve(&proxy_wallet2, &setup.swap_pool.address, &i128::MAX, &9999);
+
+    let wallets = setup
+        .swap_pool
+        .get_proxy_wallets_by_token(&setup.token_out.address);
+    assert_eq!(wallets.len(), 2);
+    assert!(wallets.contains(&setup.proxy_wallet));
+    assert!(wallets.contains(&proxy_wallet2));
+    assert_eq!(
+        setup
+            .swap_pool
+            .get_proxy_wallets_by_token(&setup.token_in.address),
+        Vec::new(e)
+    );
+
+    // both providers are able to deposit
+    setup.add_request(1, &destination, 100);
+    setup.swap_pool.add_request(
+        &setup.operator,
+        &proxy_wallet2,
+        &BytesN::from_array(e, &[2; 32]),
+        &2,
+        &destination,
+        &setup.token_in.address,
+        &100,
+    );
+    assert_eq!(setup.token_in.balance(&proxy_wallet2), 900);
+
+    setup
+        .swap_pool
+        .set_proxy_wallet_enabled(&proxy_wallet2, &false);
+    assert!(!setup.swap_pool.is_proxy_wallet_enabled(&proxy_wallet2));
+    assert!(setup.swap_pool.is_proxy_wallet_enabled(&setup.proxy_wallet));
+    assert_contract_error(
+        setup.swap_pool.try_add_request(
+            &setup.operator,
+            &proxy_wallet2,
+            &BytesN::from_array(e, &[3; 32]),
+            &3,
+            &destination,
+            &setup.token_in.address,
+            &100,
+        ),
+        PoolError::ProxyWalletDisabled as u32,
+    );
+    setup.add_request(3, &destination, 100);
+
+    setup
+        .swap_pool
+        .set_proxy_wallet_enabled(&proxy_wallet2, &true);
+    setup.swap_pool.add_request(
+        &setup.operator,
+        &proxy_wallet2,
+        &BytesN::from_array(e, &[4; 32]),
+        &4,
+        &destination,
+        &setup.token_in.address,
+        &100,
+    );
+
+    // removed wallet is no longer accepted
+    setup.swap_pool.remove_proxy_wallet(&proxy_wallet2);
+    assert_eq!(
+        setup
+            .swap_pool
+            .get_proxy_wallets_by_token(&setup.token_out.address),
+        vec![e, setup.proxy_wallet.clone()]
+    );
+    assert_contract_error(
+        setup.swap_pool.try_add_request(
+            &setup.operator,
+            &proxy_wallet2,
+            &BytesN::from_array(e, &[5; 32]),
+            &5,
+            &destination,
+            &setup.token_in.address,
+            &100,
+        ),
+        PoolError::UnauthorizedProxyWallet as u32,
+    );
+    assert_contract_error(
+        setup.swap_pool.try_remove_proxy_wallet(&proxy_wallet2),
+        PoolError::UnauthorizedProxyWallet as u32,
+    );
+}
+
+#[test]
+fn test_proxy_wallet_info() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+
+    let info = ProxyWalletInfo {
+        provider_id: Symbol::new(e, "provider"),
+        label: String::from_str(e, "Provider USDC"),
+        allowed_tokens_in: vec![e, setup.token_in.address.clone()],
+        allowed_tokens_out: Vec::new(e),
+        min_amount: 10,
+        max_amount: Some(1000),
+    };
+    assert_eq!(
+        setup.swap_pool.get_proxy_wallet_info(&setup.proxy_wallet),
+        None
+    );
+    setup
+        .swap_pool
+        .set_proxy_wallet_info(&setup.proxy_wallet, &info);
+    assert_eq!(
+        setup.swap_pool.get_proxy_wallet_info(&setup.proxy_wallet),
+        Some(info.clone())
+    );
+
+    let try_add_request = |op_id: u128, token_in: &Address, amount_in: i128| {
+        let mut tx_id = [0; 32];
+        tx_id[..16].copy_from_slice(&op_id.to_be_bytes());
+        setup.swap_pool.try_add_request(
+            &setup.operator,
+            &setup.proxy_wallet,
+            &BytesN::from_array(e, &tx_id),
+            &op_id,
+            &destination,
+            token_in,
+            &amount_in,
+        )
+    };
+    assert_contract_error(
+        try_add_request(1, &setup.token_out.address, 100),
+        PoolError::TokenInNotAllowed as u32,
+    );
+    assert_contract_error(
+        try_add_request(1, &setup.token_in.address, 9),
+        PoolError::DepositBelowMinimum as u32,
+    );
+    assert_contract_error(
+        try_add_request(1, &setup.token_in.address, 1001),
+        PoolError::DepositAboveMaximum as u32,
+    );
+    setup.add_request(1, &destination, 10);
+    setup.add_request(2, &destination, 1000);
+
+    assert_contract_error(
+        setup.swap_pool.try_set_proxy_wallet_info(
+            &setup.proxy_wallet,
+            &ProxyWalletInfo {
+                min_amount: 100,
+                max_amount: Some(10),
+                ..info.clone()
+            },
+        ),
+        PoolError::InvalidDepositLimits as u32,
+    );
+    assert_contract_error(
+        setup
+            .swap_pool
+            .try_set_proxy_wallet_info(&Address::generate(e), &info),
+        PoolError::UnauthorizedProxyWallet as u32,
+    );
+}
+
+#[test]
+fn test_destination_preferences() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    let payout_address = Address::generate(e);
+
+    setup.swap_pool.set_destination_preferences(
+        &destination,
+        &setup.token_in.address,
+        &300,
+        &Some(payout_address.clone()),
+    );
+    assert_eq!(
+        e.auths()[0].0,
+        destination.clone(),
+        "destination has to authorize its preferences"
+    );
+    assert_eq!(
+        setup.swap_pool.get_destination_preferences(&destination),
+        Some(DestinationPreferences {
+            token_out: setup.token_in.address.clone(),
+            max_slippage_bps: 300,
+            payout_address: payout_address.clone(),
+        })
+    );
+    assert_contract_error(
+        setup.swap_pool.try_set_destination_preferences(
+            &destination,
+            &setup.token_in.address,
+            &10001,
+            &None,
+        ),
+        PoolError::InvalidSlippage as u32,
+    );
+
+    // output token isn't changed unless proxy wallet allows it
+    setup.add_request(1, &destination, 100);
+    let (request, _, _) = setup.swap_pool.get_request_by_op_id(&1);
+    assert_eq!(request.token_out, setup.token_out.address);
+    assert_eq!(request.recipient, payout_address);
+    assert_eq!(request.max_slippage_bps, Some(300));
+
+    // swap result is sent to payout address
+    setup.swap_pool.swap_chained_via_router(
+        &setup.operator,
+        &destination,
+        &1,
+        &setup.swaps_chain,
+        &0,
+    );
+    assert_eq!(setup.token_out.balance(&payout_address), 98);
+    assert_eq!(setup.token_out.balance(&destination), 0);
+
+    setup.swap_pool.set_proxy_wallet_info(
+        &setup.proxy_wallet,
+        &ProxyWalletInfo {
+            provider_id: Symbol::new(e, "provider"),
+            label: String::from_str(e, "Provider"),
+            allowed_tokens_in: vec![e, setup.token_in.address.clone()],
+            allowed_tokens_out: vec![e, setup.token_in.address.clone()],
+            min_amount: 0,
+            max_amount: None,
+        },
+    );
+    setup.add_request(2, &destination, 100);
+    let (request, _, _) = setup.swap_pool.get_request_by_op_id(&2);
+    assert_eq!(request.token_out, setup.token_in.address);
+    setup
+        .swap_pool
+        .fulfill_direct(&setup.operator, &destination, &2);
+    assert_eq!(setup.token_in.balance(&payout_address), 100);
+
+    // slippage is measured against the spot price after pool fee, 1e8 out of 1e10 reserves
+    // loses ~1.27% with 0.3% pool fee, so only 50 bps limit is exceeded
+    setup.add_liquidity(10_000_000_000);
+    for (op_id, max_slippage_bps) in [(3, 50), (4, 100)] {
+        setup.swap_pool.set_destination_preferences(
+            &destination,
+            &setup.token_out.address,
+            &max_slippage_bps,
+            &None,
+        );
+        setup.add_request(op_id, &destination, 100_000_000);
+    }
+    assert_contract_error(
+        setup.swap_pool.try_swap_chained_via_router(
+            &setup.operator,
+            &destination,
+            &3,
+            &setup.swaps_chain,
+            &0,
+        ),
+        PoolError::SlippageExceeded as u32,
+    );
+    setup.swap_pool.swap_chained_via_router(
+        &setup.operator,
+        &destination,
+        &4,
+        &setup.swaps_chain,
+        &0,
+    );
+
+    // without preferences request falls back to wallet defaults
+    setup.swap_pool.remove_destination_preferences(&destination);
+    assert_eq!(
+        setup.swap_pool.get_destination_preferences(&destination),
+        None
+    );
+    setup.add_request(5, &destination, 100);
+    let (request, _, _) = setup.swap_pool.get_request_by_op_id(&5);
+    assert_eq!(request.token_out, setup.token_out.address);
+    assert_eq!(request.recipient, destination);
+    assert_eq!(request.max_slippage_bps, None);
+}
+
+#[test]
+fn test_oracle_bound() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+
+    // 100 token_in is expected to be worth 105 token_out
+    let oracle = deploy_mock_oracle(e, Some(10_500_000));
+    setup
+        .swap_pool
+        .set_price_oracle(&Some(oracle.address.clone()));
+    assert_eq!(
+        setup.swap_pool.get_price_oracle(),
+        Some(oracle.address.clone())
+    );
+    assert_eq!(setup.swap_pool.get_max_oracle_deviation(), 500);
+
+    // swap gives 98, below 105 minus 5%
+    setup.add_request(1, &destination, 100);
+    assert_contract_error(
+        setup.swap_pool.try_swap_chained_via_router(
+            &setup.operator,
+            &destination,
+            &1,
+            &setup.swaps_chain,
+            &0,
+        ),
+        PoolError::OutputDeviatesFromOracle as u32,
+    );
+
+    oracle.set_price(&None);
+    assert_contract_error(
+        setup.swap_pool.try_swap_chained_via_router(
+            &setup.operator,
+            &destination,
+            &1,
+            &setup.swaps_chain,
+            &0,
+        ),
+        PoolError::OraclePriceUnavailable as u32,
+    );
+
+    oracle.set_price(&Some(10_500_000));
+    assert_contract_error(
+        setup.swap_pool.try_set_max_oracle_deviation(&10001),
+        PoolError::InvalidOracleDeviation as u32,
+    );
+    setup.swap_pool.set_max_oracle_deviation(&1000);
+    assert_eq!(
+        setup.swap_pool.swap_chained_via_router(
+            &setup.operator,
+            &destination,
+            &1,
+            &setup.swaps_chain,
+            &0,
+        ),
+        98
+    );
+
+    // without oracle output is not bounded
+    setup.swap_pool.set_price_oracle(&None);
+    oracle.set_price(&None);
+    setup.add_request(2, &destination, 100);
+    setup.swap_pool.swap_chained_via_router(
+        &setup.operator,
+        &destination,
+        &2,
+        &setup.swaps_chain,
+        &0,
+    );
+}
+
+#[test]
+fn test_oracle_decimals() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+
+    // both tokens have 7 decimals, price of 1.05 is quoted with 14 decimals
+    let oracle = deploy_mock_oracle(e, Some(105_000_000_000_000));
+    oracle.set_decimals(&14);
+    setup
+        .swap_pool
+        .set_price_oracle(&Some(oracle.address.clone()));
+
+    // swap gives 98, below 105 minus 5%
+    setup.add_request(1, &destination, 100);
+    assert_contract_error(
+        setup.swap_pool.try_swap_chained_via_router(
+            &setup.operator,
+            &destination,
+            &1,
+            &setup.swaps_chain,
+            &0,
+        ),
+        PoolError::OutputDeviatesFromOracle as u32,
+    );
+
+    // scale out of i128 range is rejected rather than overflowing
+    oracle.set_decimals(&39);
+    assert_contract_error(
+        setup.swap_pool.try_swap_chained_via_router(
+            &setup.operator,
+            &destination,
+            &1,
+            &setup.swaps_chain,
+            &0,
+        ),
+        MathError::NumberOverflow as u32,
+    );
+
+    // price of 1.0 with 14 decimals is within the bound
+    oracle.set_decimals(&14);
+    oracle.set_price(&Some(100_000_000_000_000));
+    assert_eq!(
+        setup.swap_pool.swap_chained_via_router(
+            &setup.operator,
+            &destination,
+            &1,
+            &setup.swaps_chain,
+            &0,
+        ),
+        98
+    );
+}
+
+#[test]
+fn test_stale_oracle_price() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    e.ledger().with_mut(|li| li.timestamp = 10_000);
+
+    // swap gives 98 against 100 implied by oracle
+    let oracle = deploy_mock_oracle(e, Some(10_000_000));
+    setup
+        .swap_pool
+        .set_price_oracle(&Some(oracle.address.clone()));
+    assert_eq!(setup.swap_pool.get_max_oracle_price_age(), 900);
+    setup.add_request(1, &destination, 100);
+
+    oracle.set_timestamp(&9_099);
+    assert_contract_error(
+        setup.swap_pool.try_swap_chained_via_router(
+            &setup.operator,
+            &destination,
+            &1,
+            &setup.swaps_chain,
+            &0,
+        ),
+        PoolError::OraclePriceUnavailable as u32,
+    );
+
+    setup.swap_pool.set_max_oracle_price_age(&3600);
+    assert_eq!(setup.swap_pool.get_max_oracle_price_age(), 3600);
+    assert_eq!(
+        setup.swap_pool.swap_chained_via_router(
+            &setup.operator,
+            &destination,
+            &1,
+            &setup.swaps_chain,
+            &0,
+        ),
+        98
+    );
+}
+
+#[test]
+fn test_quote_request() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    setup
+        .swap_pool
+        .set_operational_fee(&setup.operator, &setup.token_in.address, &2);
+
+    setup.add_liquidity(10_000_000_000);
+    setup.add_request(1, &destination, 100_000_002);
+    let quote = setup
+        .swap_pool
+        .quote_request(&destination, &1, &setup.swaps_chain);
+    // 1e8 out of 1e10 reserves loses ~1% to price impact on top of 0.3% pool fee
+    assert_eq!(
+        quote,
+        RequestQuote {
+            amount_out: 98_712_871,
+            fee: 2,
+            price_impact_bps: 98,
+        }
+    );
+
+    // quote matches the actual swap
+    assert_eq!(
+        setup.swap_pool.swap_chained_via_router(
+            &setup.operator,
+            &destination,
+            &1,
+            &setup.swaps_chain,
+            &quote.amount_out,
+        ),
+        98_712_871
+    );
+    assert!(setup
+        .swap_pool
+        .try_quote_request(&destination, &1, &setup.swaps_chain)
+        .is_err());
+}
+
+#[test]
+fn test_quote_request_stableswap() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    let pair = vec![
+        e,
+        setup.token_in.address.clone(),
+        setup.token_out.address.clone(),
+    ];
+    setup
+        .router
+        .configure_init_pool_payment(&setup.token_in.address, &0, &setup.admin);
+    let (pool_index, _) = setup
+        .router
+        .init_stableswap_pool(&setup.admin, &pair, &85, &6, &0);
+    SorobanTokenAdminClient::new(e, &setup.token_in.address).mint(&setup.admin, &20_000_000_000);
+    SorobanTokenAdminClient::new(e, &setup.token_out.address).mint(&setup.admin, &10_000_000_000);
+    setup.router.deposit(
+        &setup.admin,
+        &pair,
+        &pool_index,
+        &vec![e, 20_000_000_000, 10_000_000_000],
+        &0,
+    );
+    let swaps_chain = vec![
+        e,
+        (pair.clone(), pool_index, setup.token_out.address.clone()),
+    ];
+    SorobanTokenAdminClient::new(e, &setup.token_in.address)
+        .mint(&setup.proxy_wallet, &100_000_000);
+    setup.add_request(1, &destination, 100_000_000);
+
+    // marginal price of imbalanced stableswap pool stays near 1 despite 2:1 reserves
+    let quote = setup
+        .swap_pool
+        .quote_request(&destination, &1, &swaps_chain);
+    assert_eq!(
+        quote,
+        RequestQuote {
+            amount_out: 98_954_940,
+            fee: 0,
+            price_impact_bps: 1,
+        }
+    );
+    assert_eq!(
+        setup.swap_pool.swap_chained_via_router(
+            &setup.operator,
+            &destination,
+            &1,
+            &swaps_chain,
+            &quote.amount_out,
+        ),
+        98_954_940
+    );
+}
+
+#[test]
+fn test_swap_via_venue() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    let adapter = deploy_mock_adapter(e, &setup.token_out.address);
+
+    setup
+        .swap_pool
+        .register_venue(&1, &Venue::Pool(setup.liquidity_pool.clone()));
+    setup
+        .swap_pool
+        .register_venue(&2, &Venue::Adapter(adapter.clone()));
+    assert_eq!(
+        setup.swap_pool.get_venues(),
+        Map::from_array(
+            e,
+            [
+                (1, Venue::Pool(setup.liquidity_pool.clone())),
+                (2, Venue::Adapter(adapter.clone())),
+            ]
+        )
+    );
+
+    setup.add_request(1, &destination, 100);
+    setup.add_request(2, &destination, 100);
+    assert_contract_error(
+        setup
+            .swap_pool
+            .try_swap_via_venue(&setup.operator, &destination, &1, &3, &Vec::new(e), &0),
+        PoolError::VenueNotFound as u32,
+    );
+
+    // single pool venue doesn't need swaps chain
+    assert_eq!(
+        setup
+            .swap_pool
+            .swap_via_venue(&setup.operator, &destination, &1, &1, &Vec::new(e), &0,),
+        98
+    );
+    assert_eq!(
+        setup
+            .swap_pool
+            .swap_via_venue(&setup.operator, &destination, &2, &2, &Vec::new(e), &50,),
+        50
+    );
+    assert_eq!(setup.token_out.balance(&destination), 148);
+    assert_eq!(setup.token_in.balance(&adapter), 100);
+    assert_eq!(setup.token_out.balance(&setup.swap_pool.address), 0);
+
+    // adapter spot price is probed with its own estimate, so slippage limit applies to it as well
+    setup.swap_pool.set_destination_preferences(
+        &destination,
+        &setup.token_out.address,
+        &100,
+        &None,
+    );
+    setup.add_request(4, &destination, 100);
+    assert_eq!(
+        setup
+            .swap_pool
+            .swap_via_venue(&setup.operator, &destination, &4, &2, &Vec::new(e), &0),
+        50
+    );
+    assert_eq!(setup.token_out.balance(&destination), 198);
+
+    setup.swap_pool.remove_venue(&2);
+    assert_eq!(setup.swap_pool.get_venues().len(), 1);
+    assert_contract_error(
+        setup.swap_pool.try_remove_venue(&2),
+        PoolError::VenueNotFound as u32,
+    );
+}
+
+#[test]
+fn test_swap_best_execution() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    let adapter = deploy_mock_adapter(e, &setup.token_out.address);
+    setup
+        .swap_pool
+        .register_venue(&1, &Venue::Adapter(adapter.clone()));
+    setup
+        .swap_pool
+        .register_venue(&2, &Venue::Pool(setup.liquidity_pool.clone()));
+    let route = |venue_id: u32| SwapRoute {
+        venue_id,
+        swaps_chain: Vec::new(e),
+    };
+
+    setup.add_request(1, &destination, 100);
+    assert_contract_error(
+        setup.swap_pool.try_swap_best_execution(
+            &setup.operator,
+            &destination,
+            &1,
+            &Vec::new(e),
+            &0,
+        ),
+        PoolError::NoRoutesProvided as u32,
+    );
+
+    // unregistered venue and venue failing to estimate are skipped
+    setup
+        .swap_pool
+        .register_venue(&3, &Venue::Pool(Address::generate(e)));
+    assert_contract_error(
+        setup.swap_pool.try_swap_best_execution(
+            &setup.operator,
+            &destination,
+            &1,
+            &vec![e, route(4), route(3)],
+            &0,
+        ),
+        PoolError::NoViableRoute as u32,
+    );
+
+    // pool quotes 98 while adapter quotes 50
+    assert_eq!(
+        setup.swap_pool.swap_best_execution(
+            &setup.operator,
+            &destination,
+            &1,
+            &vec![e, route(4), route(3), route(1), route(2)],
+            &0,
+        ),
+        98
+    );
+    assert_eq!(setup.token_out.balance(&destination), 98);
+    assert_eq!(setup.token_in.balance(&adapter), 0);
+
+    // venue used is recorded, configured router is not a registered venue
+    setup.add_request(2, &destination, 100);
+    setup.swap_pool.swap_chained_via_router(
+        &setup.operator,
+        &destination,
+        &2,
+        &setup.swaps_chain,
+        &0,
+    );
+    let completed = setup
+        .swap_pool
+        .get_completed_requests_details(&destination, &0);
+    assert_eq!(completed.get(0).unwrap().venue_id, Some(2));
+    assert_eq!(completed.get(1).unwrap().venue_id, None);
+
+    // 1e5 out of 1e6 reserves loses ~9% to price impact, so pool's better quote exceeds 1% slippage
+    setup.add_liquidity(990_000);
+    SorobanTokenAdminClient::new(e, &setup.token_out.address).mint(&adapter, &100_000);
+    setup.swap_pool.set_destination_preferences(
+        &destination,
+        &setup.token_out.address,
+        &100,
+        &None,
+    );
+    setup.add_request(3, &destination, 100_000);
+    assert_contract_error(
+        setup.swap_pool.try_swap_best_execution(
+            &setup.operator,
+            &destination,
+            &3,
+            &vec![e, route(2)],
+            &0,
+        ),
+        PoolError::SlippageExceeded as u32,
+    );
+
+    // adapter's flat curve is checked against pool's spot price too, so it's no fallback
+    assert_contract_error(
+        setup.swap_pool.try_swap_best_execution(
+            &setup.operator,
+            &destination,
+            &3,
+            &vec![e, route(2), route(1)],
+            &0,
+        ),
+        PoolError::SlippageExceeded as u32,
+    );
+    assert_contract_error(
+        setup.swap_pool.try_swap_best_execution(
+            &setup.operator,
+            &destination,
+            &3,
+            &vec![e, route(1), route(2)],
+            &0,
+        ),
+        PoolError::SlippageExceeded as u32,
+    );
+
+    // on its own adapter is within slippage of its spot price
+    assert_eq!(
+        setup.swap_pool.swap_best_execution(
+            &setup.operator,
+            &destination,
+            &3,
+            &vec![e, route(1)],
+            &0,
+        ),
+        50_000
+    );
+    let completed = setup
+        .swap_pool
+        .get_completed_requests_details(&destination, &0);
+    assert_eq!(completed.get(2).unwrap().venue_id, Some(1));
+}
+
+#[test]
+fn test_legacy_completed_requests() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    assert_eq!(setup.swap_pool.version(), 105);
+
+    // partially filled page left by the previous version
+    let legacy_request = LegacyCompletedSwapRequest {
+        tx_id: BytesN::from_array(e, &[7; 32]),
+        op_id: 0,
+        destination: destination.clone(),
+        token_in: setup.token_in.address.clone(),
+        amount_in: 50,
+        token_out: setup.token_out.address.clone(),
+        amount_out: 49,
+    };
+    e.as_contract(&setup.swap_pool.address, || {
+        e.storage().persistent().set(
+            &legacy::DataKey::CompletedSwapRequests(destination.clone(), 0),
+            &vec![e, legacy_request.clone()],
+        );
+        e.storage().persistent().set(
+            &legacy::DataKey::CompletedSwapRequestLastPage(destination.clone()),
+            &0_u32,
+        );
+    });
+
+    setup.add_request(1, &destination, 100);
+    let amount_out = setup.swap_pool.swap_chained_via_router(
+        &setup.operator,
+        &destination,
+        &1,
+        &setup.swaps_chain,
+        &90,
+    );
+
+    // legacy page stays readable, new record starts the next page
+    assert_eq!(
+        setup
+            .swap_pool
+            .get_completed_requests_last_page(&destination),
+        1
+    );
+    assert_eq!(
+        setup
+            .swap_pool
+            .get_completed_requests_details(&destination, &0),
+        vec![
+            e,
+            CompletedSwapRequest {
+                tx_id: legacy_request.tx_id,
+                op_id: 0,
+                destination: destination.clone(),
+                token_in: setup.token_in.address.clone(),
+                amount_in: 50,
+                token_out: setup.token_out.address.clone(),
+                amount_out: 49,
+                status: RequestStatus::Completed,
+                venue_id: None,
+            }
+        ]
+    );
+    let page = setup.swap_pool.get_completed_requests(&destination, &1);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get(0).unwrap().1, 1);
+    assert_eq!(page.get(0).unwrap().6, amount_out);
+}
+
+#[test]
+fn test_legacy_operator() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    let legacy_operator = Address::generate(e);
+    e.as_contract(&setup.swap_pool.address, || {
+        e.storage()
+            .instance()
+            .set(&legacy::DataKey::Operator, &legacy_operator);
+    });
+
+    // views report legacy operator without migrating it
+    assert!(setup.swap_pool.has_role(&Role::Operator, &legacy_operator));
+    assert_eq!(
+        setup.swap_pool.get_role_members(&Role::Operator),
+        vec![e, setup.operator.clone(), legacy_operator.clone()]
+    );
+    e.as_contract(&setup.swap_pool.address, || {
+        assert!(e.storage().instance().has(&legacy::DataKey::Operator));
+    });
+
+    // legacy operator is able to cancel requests before any other call migrates it
+    setup.add_request(1, &destination, 100);
+    setup.swap_pool.cancel_request(
+        &legacy_operator,
+        &destination,
+        &1,
+        &setup.proxy_wallet,
+        &false,
+    );
+    e.as_contract(&setup.swap_pool.address, || {
+        assert!(!e.storage().instance().has(&legacy::DataKey::Operator));
+    });
+
+    // operator stored by the previous version keeps working and is moved to the role
+    e.as_contract(&setup.swap_pool.address, || {
+        e.storage()
+            .instance()
+            .set(&legacy::DataKey::Operator, &legacy_operator);
+    });
+    setup.swap_pool.add_request(
+        &legacy_operator,
+        &setup.proxy_wallet,
+        &BytesN::from_array(e, &[1; 32]),
+        &2,
+        &destination,
+        &setup.token_in.address,
+        &100,
+    );
+    assert_eq!(
+        setup.swap_pool.get_role_members(&Role::Operator),
+        vec![e, setup.operator.clone(), legacy_operator.clone()]
+    );
+
+    // revoking it before first use doesn't let it come back
+    let stale_operator = Address::generate(e);
+    e.as_contract(&setup.swap_pool.address, || {
+        e.storage()
+            .instance()
+            .set(&legacy::DataKey::Operator, &stale_operator);
+    });
+    setup
+        .swap_pool
+        .revoke_role(&Role::Operator, &stale_operator);
+    assert_contract_error(
+        setup.swap_pool.try_add_request(
+            &stale_operator,
+            &setup.proxy_wallet,
+            &BytesN::from_array(e, &[2; 32]),
+            &3,
+            &destination,
+            &setup.token_in.address,
+            &100,
+        ),
+        PoolError::UnauthorizedOperator as u32,
+    );
+}
+
+#[test]
+fn test_legacy_operational_fee() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+    let token = create_token_contract(e, &setup.admin);
+    SorobanTokenAdminClient::new(e, &token.address).mint(&setup.proxy_wallet, &1000);
+    token.approve(&setup.proxy_wallet, &setup.swap_pool.address, &1000, &9999);
+
+    // flat fee stored by the previous version applies until schedule is set
+    e.as_contract(&setup.swap_pool.address, || {
+        e.storage().persistent().set(
+            &legacy::DataKey::OperationalFee(token.address.clone()),
+            &5_i128,
+        );
+    });
+    assert_eq!(
+        setup.swap_pool.get_operational_fee_schedule(&token.address),
+        FeeSchedule::from_flat(5)
+    );
+    assert_eq!(
+        setup.swap_pool.quote_operational_fee(&token.address, &100),
+        5
+    );
+    setup.swap_pool.add_request(
+        &setup.operator,
+        &setup.proxy_wallet,
+        &BytesN::from_array(e, &[1; 32]),
+        &1,
+        &destination,
+        &token.address,
+        &100,
+    );
+    assert_eq!(setup.swap_pool.get_accumulated_fees(&token.address), 5);
+
+    setup
+        .swap_pool
+        .set_operational_fee(&setup.operator, &token.address, &3);
+    assert_eq!(setup.swap_pool.get_operational_fee(&token.address), 3);
+}
+
+#[test]
+fn test_migrate_legacy_requests() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+
+    // requests pending at upgrade time, deposits are held by the contract
+    let mut legacy_requests = Vec::new(e);
+    for op_id in 1..=3_u128 {
+        legacy_requests.push_back(LegacySwapRequest {
+            tx_id: BytesN::from_array(e, &[op_id as u8; 32]),
+            op_id,
+            destination: destination.clone(),
+            token_in: setup.token_in.address.clone(),
+            amount_in: 100,
+            token_out: setup.token_out.address.clone(),
+        });
+    }
+    SorobanTokenAdminClient::new(e, &setup.token_in.address).mint(&setup.swap_pool.address, &300);
+    e.as_contract(&setup.swap_pool.address, || {
+        e.storage().persistent().set(
+            &legacy::DataKey::SwapRequests(destination.clone()),
+            &legacy_requests,
+        );
+        e.storage().persistent().set(
+            &legacy::DataKey::DestinationsList(0),
+            &vec![e, destination.clone()],
+        );
+        e.storage()
+            .instance()
+            .set(&legacy::DataKey::LastOperationId, &3_u128);
+    });
+
+    // destination known before the upgrade isn't listed again
+    setup.add_request(4, &destination, 100);
+    assert_eq!(
+        setup.swap_pool.get_destinations(&0),
+        vec![e, destination.clone()]
+    );
+
+    // requests pending since before the upgrade are listed until they're migrated
+    assert_eq!(
+        setup
+            .swap_pool
+            .get_requests(&destination)
+            .iter()
+            .map(|r| r.1)
+            .collect::<std::vec::Vec<_>>(),
+        std::vec![4, 1, 2, 3]
+    );
+
+    // wallet that took the deposits isn't mapped to the token anymore
+    let refund_wallet = Address::generate(e);
+    setup.swap_pool.remove_proxy_wallet(&setup.proxy_wallet);
+    assert_eq!(
+        setup
+            .swap_pool
+            .migrate_legacy_requests(&destination, &refund_wallet, &2),
+        1
+    );
+    assert_eq!(
+        setup
+            .swap_pool
+            .migrate_legacy_requests(&destination, &refund_wallet, &2),
+        0
+    );
+    assert_eq!(
+        setup
+            .swap_pool
+            .get_requests(&destination)
+            .iter()
+            .map(|r| r.1)
+            .collect::<std::vec::Vec<_>>(),
+        std::vec![4, 1, 2, 3]
+    );
+    let (request, status, _) = setup
+        .swap_pool
+        .get_request_by_tx_id(&BytesN::from_array(e, &[2; 32]));
+    assert_eq!(status, RequestStatus::Pending);
+    assert_eq!(request.op_id, 2);
+    assert_eq!(request.fee, 0);
+    assert_eq!(request.recipient, destination);
+    assert_eq!(request.proxy_wallet, refund_wallet);
+
+    // migrated requests are processed as usual
+    let amount_out = setup.swap_pool.swap_chained_via_router(
+        &setup.operator,
+        &destination,
+        &1,
+        &setup.swaps_chain,
+        &90,
+    );
+    assert_eq!(setup.token_out.balance(&destination), amount_out);
+    setup
+        .swap_pool
+        .cancel_request(&setup.operator, &destination, &2, &refund_wallet, &false);
+    assert_eq!(setup.token_in.balance(&refund_wallet), 100);
+    assert_eq!(
+        setup
+            .swap_pool
+            .get_requests(&destination)
+            .iter()
+            .map(|r| r.1)
+            .collect::<std::vec::Vec<_>>(),
+        std::vec![4, 3]
+    );
+
+    // list of pending requests is kept in order as its ends are removed and added
+    setup
+        .swap_pool
+        .cancel_request(&setup.admin, &destination, &4, &refund_wallet, &false);
+    setup
+        .swap_pool
+        .cancel_request(&setup.admin, &destination, &3, &refund_wallet, &false);
+    assert_eq!(setup.swap_pool.get_requests(&destination), Vec::new(e));
+    setup
+        .swap_pool
+        .add_proxy_wallet(&setup.proxy_wallet, &setup.token_out.address);
+    setup.add_request(5, &destination, 100);
+    setup.add_request(6, &destination, 100);
+    assert_eq!(
+        setup
+            .swap_pool
+            .get_requests(&destination)
+            .iter()
+            .map(|r| r.1)
+            .collect::<std::vec::Vec<_>>(),
+        std::vec![5, 6]
+    );
+    assert_eq!(
+        setup.swap_pool.get_destinations(&0),
+        vec![e, destination.clone()]
+    );
+}
+
+#[test]
+fn test_operation_id_window_floor() {
+    let setup = Setup::default();
+    let e = &setup.e;
+    let destination = Address::generate(e);
+
+    // ids consumed before the upgrade are tracked by the last operation id only
+    e.as_contract(&setup.swap_pool.address, || {
+        e.storage()
+            .instance()
+            .set(&legacy::DataKey::LastOperationId, &10_u128);
+    });
+    setup.swap_pool.set_operation_id_window(&5);
+    setup.add_request(12, &destination, 10);
+
+    let try_add_request = |op_id: u128| {
+        let mut tx_id = [0; 32];
+        tx_id[..16].copy_from_slice(&op_id.to_be_bytes());
+        setup.swap_pool.try_add_request(
+            &setup.operator,
+            &setup.proxy_wallet,
+            &BytesN::from_array(e, &tx_id),
+            &op_id,
+            &destination,
+            &setup.token_in.address,
+            &10,
+        )
+    };
+    for op_id in [7, 10] {
+        assert_contract_error(
+            try_add_request(op_id),
+            PoolError::OperationIdAlreadyConsumed as u32,
+        );
+    }
+    setup.add_request(11, &destination, 10);
+
+    // floor is kept when the window is changed later
+    setup.swap_pool.set_operation_id_window(&0);
+    setup.swap_pool.set_operation_id_window(&20);
+    assert_contract_error(
+        try_add_request(9),
+        PoolError::OperationIdAlreadyConsumed as u32,
+    );
+    setup.add_request(13, &destination, 10);
+    assert_eq!(setup.swap_pool.get_last_operation_id(), 13);
+}
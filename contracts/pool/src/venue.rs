@@ -0,0 +1,420 @@
+use crate::errors::PoolError;
+use crate::swap_router::{
+    authorize_transfer, estimate_swaps_chain, probe_amount_out, swap_with_router,
+    try_estimate_swaps_chain, validate_swaps_chain,
+};
+use soroban_sdk::{contractclient, contracttype, panic_with_error, Address, BytesN, Env, Vec};
+use utils::math_errors::MathError;
+
+pub mod liquidity_pool {
+    soroban_sdk::contractimport!(file = "../../wasm/soroban_liquidity_pool_contract.wasm");
+}
+
+// generic venue interface: adapter takes token_in from the user with SEP-41 transfer
+// and sends token_out back to the user
+#[allow(dead_code)]
+#[contractclient(name = "SwapAdapterClient")]
+pub trait SwapAdapter {
+    fn estimate_swap(e: Env, token_in: Address, token_out: Address, in_amount: u128) -> u128;
+    fn swap(
+        e: Env,
+        user: Address,
+        token_in: Address,
+        token_out: Address,
+        in_amount: u128,
+        out_min: u128,
+    ) -> u128;
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Venue {
+    Router(Address),
+    Pool(Address),
+    Adapter(Address),
+}
+
+// candidate route for best execution
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SwapRoute {
+    pub venue_id: u32,
+    pub swaps_chain: Vec<(Vec<Address>, BytesN<32>, Address)>,
+}
+
+// swaps chain is used only by the router venue, other venues swap the pair directly
+pub(crate) trait SwapVenue {
+    fn validate(
+        &self,
+        e: &Env,
+        swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+        token_in: &Address,
+        token_out: &Address,
+    );
+    fn estimate(
+        &self,
+        e: &Env,
+        swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+        token_in: &Address,
+        token_out: &Address,
+        in_amount: u128,
+    ) -> u128;
+    // output without price impact, probed with the venue's own estimate, so any venue
+    // provides it regardless of how it prices swaps. venue fees are included
+    fn spot_amount_out(
+        &self,
+        e: &Env,
+        swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+        token_in: &Address,
+        token_out: &Address,
+        in_amount: u128,
+    ) -> u128 {
+        match probe_amount_out(in_amount, |amount| {
+            Some(self.estimate(e, swaps_chain, token_in, token_out, amount))
+        }) {
+            Some(v) => v,
+            None => panic_with_error!(e, MathError::NumberOverflow),
+        }
+    }
+    // non-reverting estimate to compare candidates with, none if venue can't swap the pair
+    fn try_estimate(
+        &self,
+        e: &Env,
+        swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+        token_in: &Address,
+        token_out: &Address,
+        in_amount: u128,
+    ) -> Option<u128>;
+    fn try_spot_amount_out(
+        &self,
+        e: &Env,
+        swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+        token_in: &Address,
+        token_out: &Address,
+        in_amount: u128,
+    ) -> Option<u128> {
+        probe_amount_out(in_amount, |amount| {
+            self.try_estimate(e, swaps_chain, token_in, token_out, amount)
+        })
+    }
+    fn swap(
+        &self,
+        e: &Env,
+        swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+        token_in: &Address,
+        token_out: &Address,
+        in_amount: u128,
+        out_min: u128,
+    ) -> u128;
+}
+
+pub(crate) struct RouterVenue(pub Address);
+pub(crate) struct PoolVenue(pub Address);
+pub(crate) struct AdapterVenue(pub Address);
+
+impl SwapVenue for RouterVenue {
+    fn validate(
+        &self,
+        e: &Env,
+        swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+        token_in: &Address,
+        token_out: &Address,
+    ) {
+        validate_swaps_chain(e, swaps_chain, token_in, token_out);
+    }
+
+    fn estimate(
+        &self,
+        e: &Env,
+        swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+        token_in: &Address,
+        _token_out: &Address,
+        in_amount: u128,
+    ) -> u128 {
+        estimate_swaps_chain(e, &self.0, swaps_chain, token_in, in_amount)
+    }
+
+    fn try_estimate(
+        &self,
+        e: &Env,
+        swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+        token_in: &Address,
+        token_out: &Address,
+        in_amount: u128,
+    ) -> Option<u128> {
+        try_estimate_swaps_chain(e, &self.0, swaps_chain, token_in, token_out, in_amount)
+    }
+
+    fn swap(
+        &self,
+        e: &Env,
+        swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+        token_in: &Address,
+        _token_out: &Address,
+        in_amount: u128,
+        out_min: u128,
+    ) -> u128 {
+        swap_with_router(e, &self.0, swaps_chain, token_in, &in_amount, &out_min)
+    }
+}
+
+fn find_pair_indexes(
+    tokens: &Vec<Address>,
+    token_in: &Address,
+    token_out: &Address,
+) -> Option<(u32, u32)> {
+    match (
+        tokens.first_index_of(token_in),
+        tokens.first_index_of(token_out),
+    ) {
+        (Some(in_idx), Some(out_idx)) if in_idx != out_idx => Some((in_idx, out_idx)),
+        _ => None,
+    }
+}
+
+impl PoolVenue {
+    fn pair_indexes(&self, e: &Env, token_in: &Address, token_out: &Address) -> (u32, u32) {
+        let tokens = liquidity_pool::Client::new(e, &self.0).get_tokens();
+        match find_pair_indexes(&tokens, token_in, token_out) {
+            Some(v) => v,
+            None => panic_with_error!(e, PoolError::VenuePairNotSupported),
+        }
+    }
+}
+
+impl SwapVenue for PoolVenue {
+    fn validate(
+        &self,
+        e: &Env,
+        _swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+        token_in: &Address,
+        token_out: &Address,
+    ) {
+        self.pair_indexes(e, token_in, token_out);
+    }
+
+    fn estimate(
+        &self,
+        e: &Env,
+        _swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+        token_in: &Address,
+        token_out: &Address,
+        in_amount: u128,
+    ) -> u128 {
+        let (in_idx, out_idx) = self.pair_indexes(e, token_in, token_out);
+        liquidity_pool::Client::new(e, &self.0).estimate_swap(&in_idx, &out_idx, &in_amount)
+    }
+
+    fn try_estimate(
+        &self,
+        e: &Env,
+        _swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+        token_in: &Address,
+        token_out: &Address,
+        in_amount: u128,
+    ) -> Option<u128> {
+        let client = liquidity_pool::Client::new(e, &self.0);
+        let tokens = client.try_get_tokens().ok()?.ok()?;
+        let (in_idx, out_idx) = find_pair_indexes(&tokens, token_in, token_out)?;
+        client
+            .try_estimate_swap(&in_idx, &out_idx, &in_amount)
+            .ok()?
+            .ok()
+    }
+
+    fn swap(
+        &self,
+        e: &Env,
+        _swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+        token_in: &Address,
+        token_out: &Address,
+        in_amount: u128,
+        out_min: u128,
+    ) -> u128 {
+        let (in_idx, out_idx) = self.pair_indexes(e, token_in, token_out);
+        authorize_transfer(e, token_in, &self.0, in_amount as i128);
+        liquidity_pool::Client::new(e, &self.0).swap(
+            &e.current_contract_address(),
+            &in_idx,
+            &out_idx,
+            &in_amount,
+            &out_min,
+        )
+    }
+}
+
+impl SwapVenue for AdapterVenue {
+    fn validate(
+        &self,
+        _e: &Env,
+        _swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+        _token_in: &Address,
+        _token_out: &Address,
+    ) {
+    }
+
+    fn estimate(
+        &self,
+        e: &Env,
+        _swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+        token_in: &Address,
+        token_out: &Address,
+        in_amount: u128,
+    ) -> u128 {
+        SwapAdapterClient::new(e, &self.0).estimate_swap(token_in, token_out, &in_amount)
+    }
+
+    fn try_estimate(
+        &self,
+        e: &Env,
+        _swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+        token_in: &Address,
+        token_out: &Address,
+        in_amount: u128,
+    ) -> Option<u128> {
+        SwapAdapterClient::new(e, &self.0)
+            .try_estimate_swap(token_in, token_out, &in_amount)
+            .ok()?
+            .ok()
+    }
+
+    fn swap(
+        &self,
+        e: &Env,
+        _swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+        token_in: &Address,
+        token_out: &Address,
+        in_amount: u128,
+        out_min: u128,
+    ) -> u128 {
+        authorize_transfer(e, token_in, &self.0, in_amount as i128);
+        SwapAdapterClient::new(e, &self.0).swap(
+            &e.current_contract_address(),
+            token_in,
+            token_out,
+            &in_amount,
+            &out_min,
+        )
+    }
+}
+
+impl SwapVenue for Venue {
+    fn validate(
+        &self,
+        e: &Env,
+        swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+        token_in: &Address,
+        token_out: &Address,
+    ) {
+        match self {
+            Venue::Router(address) => {
+                RouterVenue(address.clone()).validate(e, swaps_chain, token_in, token_out)
+            }
+            Venue::Pool(address) => {
+                PoolVenue(address.clone()).validate(e, swaps_chain, token_in, token_out)
+            }
+            Venue::Adapter(address) => {
+                AdapterVenue(address.clone()).validate(e, swaps_chain, token_in, token_out)
+            }
+        }
+    }
+
+    fn estimate(
+        &self,
+        e: &Env,
+        swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+        token_in: &Address,
+        token_out: &Address,
+        in_amount: u128,
+    ) -> u128 {
+        match self {
+            Venue::Router(address) => RouterVenue(address.clone()).estimate(
+                e,
+                swaps_chain,
+                token_in,
+                token_out,
+                in_amount,
+            ),
+            Venue::Pool(address) => {
+                PoolVenue(address.clone()).estimate(e, swaps_chain, token_in, token_out, in_amount)
+            }
+            Venue::Adapter(address) => AdapterVenue(address.clone()).estimate(
+                e,
+                swaps_chain,
+                token_in,
+                token_out,
+                in_amount,
+            ),
+        }
+    }
+
+    fn try_estimate(
+        &self,
+        e: &Env,
+        swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+        token_in: &Address,
+        token_out: &Address,
+        in_amount: u128,
+    ) -> Option<u128> {
+        match self {
+            Venue::Router(address) => RouterVenue(address.clone()).try_estimate(
+                e,
+                swaps_chain,
+                token_in,
+                token_out,
+                in_amount,
+            ),
+            Venue::Pool(address) => PoolVenue(address.clone()).try_estimate(
+                e,
+                swaps_chain,
+                token_in,
+                token_out,
+                in_amount,
+            ),
+            Venue::Adapter(address) => AdapterVenue(address.clone()).try_estimate(
+                e,
+                swaps_chain,
+                token_in,
+                token_out,
+                in_amount,
+            ),
+        }
+    }
+
+    fn swap(
+        &self,
+        e: &Env,
+        swaps_chain: &Vec<(Vec<Address>, BytesN<32>, Address)>,
+        token_in: &Address,
+        token_out: &Address,
+        in_amount: u128,
+        out_min: u128,
+    ) -> u128 {
+        match self {
+            Venue::Router(address) => RouterVenue(address.clone()).swap(
+                e,
+                swaps_chain,
+                token_in,
+                token_out,
+                in_amount,
+                out_min,
+            ),
+            Venue::Pool(address) => PoolVenue(address.clone()).swap(
+                e,
+                swaps_chain,
+                token_in,
+                token_out,
+                in_amount,
+                out_min,
+            ),
+            Venue::Adapter(address) => AdapterVenue(address.clone()).swap(
+                e,
+                swaps_chain,
+                token_in,
+                token_out,
+                in_amount,
+                out_min,
+            ),
+        }
+    }
+}
@@ -1 +1,2 @@
 pub const DAY_IN_LEDGERS: u32 = 17280;
+pub const BASIS_POINTS: u32 = 10000;
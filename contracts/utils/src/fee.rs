@@ -0,0 +1,46 @@
+use crate::constant::BASIS_POINTS;
+use crate::math_errors::MathError;
+use soroban_sdk::{contracttype, panic_with_error, Env};
+
+// fee = clamp(flat + amount * rate_bps / BASIS_POINTS, min, max)
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeSchedule {
+    pub flat: i128,
+    pub rate_bps: u32,
+    pub min: i128,
+    pub max: Option<i128>,
+}
+
+impl FeeSchedule {
+    pub fn from_flat(fee: i128) -> FeeSchedule {
+        FeeSchedule {
+            flat: fee,
+            rate_bps: 0,
+            min: 0,
+            max: None,
+        }
+    }
+}
+
+pub fn calculate_fee(e: &Env, schedule: &FeeSchedule, amount: i128) -> i128 {
+    // percentage part is rounded down in favor of the user
+    let variable = match amount.checked_mul(schedule.rate_bps as i128) {
+        Some(v) => v / BASIS_POINTS as i128,
+        None => panic_with_error!(e, MathError::NumberOverflow),
+    };
+    let mut fee = match schedule.flat.checked_add(variable) {
+        Some(v) => v,
+        None => panic_with_error!(e, MathError::NumberOverflow),
+    };
+
+    if fee < schedule.min {
+        fee = schedule.min;
+    }
+    if let Some(max) = schedule.max {
+        if fee > max {
+            fee = max;
+        }
+    }
+    fee
+}
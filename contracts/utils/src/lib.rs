@@ -2,6 +2,7 @@
 
 pub mod bump;
 pub mod constant;
+pub mod fee;
 pub mod math_errors;
 pub mod storage;
 pub mod storage_errors;